@@ -1,39 +1,133 @@
-/// One integer per a line with groups divided by blank lines
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::{self, BufRead};
+use serde::Serialize;
+use crate::utils::params;
+
+/// The totals for one elf's group of items.
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
+pub struct Elf {
+  /// The elf's position in the input, starting at 0.
+  pub index: usize,
+  pub total: i32,
+  /// The number of items the elf is carrying.
+  pub items: usize,
+}
+
+impl fmt::Display for Elf {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "elf {} carries {} calories in {} items", self.index, self.total, self.items)
+  }
+}
+
+/// Read the elves one at a time from one integer per a line with groups
+/// divided by blank lines, so only the current elf is in memory.
 ///   1
 ///   2
 ///
 ///   3
-/// = vec!{vec!{1, 2}, vec!{3}}
-pub fn generator(input: &str) -> Vec<Vec<i32>> {
-  input.split("\n\n")
-    .map(|section|
-       section.lines().filter_map(|line| line.parse().ok()).collect())
+/// = Elf{index: 0, total: 3, items: 2}, Elf{index: 1, total: 3, items: 1}
+pub struct Elves<R> {
+  lines: io::Lines<R>,
+  index: usize,
+}
+
+impl<R: BufRead> Elves<R> {
+  pub fn new(reader: R) -> Self {
+    Elves{lines: reader.lines(), index: 0}
+  }
+}
+
+impl<R: BufRead> Iterator for Elves<R> {
+  type Item = io::Result<Elf>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut elf: Option<Elf> = None;
+    for line in self.lines.by_ref() {
+      let line = match line {
+        Ok(line) => line,
+        Err(e) => return Some(Err(e)),
+      };
+      if line.trim().is_empty() {
+        if elf.is_some() {
+          break
+        }
+        continue
+      }
+      let calories: i32 = match line.trim().parse() {
+        Ok(calories) => calories,
+        Err(_) => return Some(Err(io::Error::new(io::ErrorKind::InvalidData,
+                                                 format!("Can't parse '{line}'")))),
+      };
+      let current = elf.get_or_insert(Elf{index: self.index, total: 0, items: 0});
+      current.total += calories;
+      current.items += 1;
+    }
+    if elf.is_some() {
+      self.index += 1;
+    }
+    elf.map(Ok)
+  }
+}
+
+/// Find the k elves carrying the most calories, keeping only k of them in
+/// memory. They are ranked from the most calories down, with ties going to
+/// the elf that came first.
+pub fn top_k(elves: impl IntoIterator<Item=Elf>, k: usize) -> Vec<Elf> {
+  // a min-heap of the best so far, so the one to drop is on top. It grows
+  // as needed, since k may be much larger than the number of elves.
+  let mut heap: BinaryHeap<Reverse<(i32, Reverse<usize>, usize)>> = BinaryHeap::new();
+  for elf in elves {
+    heap.push(Reverse((elf.total, Reverse(elf.index), elf.items)));
+    if heap.len() > k {
+      heap.pop();
+    }
+  }
+  heap.into_sorted_vec().into_iter()
+    .map(|Reverse((total, Reverse(index), items))| Elf{index, total, items})
     .collect()
 }
 
-/// Sum each group and find the maximum
-pub fn part1(input: &[Vec<i32>]) -> i32 {
-  input.iter()
-    .map(|v| v.iter().sum())
-    .max().unwrap()
+/// The number of elves whose calories part 2 adds, which the runner can
+/// change with the `day1.top` parameter.
+const DEFAULT_TOP: usize = 3;
+
+/// The number of elves that part 2 adds up.
+fn top() -> usize {
+  params::get("day1.top", DEFAULT_TOP)
+}
+
+/// Stream the elves and keep only the ones that the parts need, from the
+/// most calories down.
+pub fn generator(input: &str) -> Vec<Elf> {
+  let elves = Elves::new(input.as_bytes())
+    .map(|elf| elf.unwrap_or_else(|e| panic!("Can't read elves: {e}")));
+  top_k(elves, top().max(1))
+}
+
+/// Find the elf with the most calories
+pub fn part1(input: &[Elf]) -> i32 {
+  input[0].total
 }
 
-/// Add the three largest groups
-pub fn part2(input: &[Vec<i32>]) -> i32 {
-  let mut calories: Vec<i32> = input.iter()
-    .map(|v| v.iter().sum()).collect();
-  calories.sort_unstable_by(|a, b| b.cmp(a));
-  calories.iter().take(3).sum()
+/// Add the calories of the top elves
+pub fn part2(input: &[Elf]) -> i32 {
+  input.iter().take(top()).map(|elf| elf.total).sum()
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day1::{generator, part1, part2};
+  use std::collections::BTreeMap;
+  use crate::day1::{generator, part1, part2, top_k, Elf, Elves};
+  use crate::utils::params;
 
   #[test]
   fn parsing_test() {
-    let result= generator("1\n2\n\n3\n4\n5");
-    assert_eq!(vec!{vec!{1, 2}, vec!{3, 4, 5}}, result);
+    let result = generator("1\n2\n\n3\n4\n5\n\n\n6");
+    assert_eq!(vec![Elf{index: 1, total: 12, items: 3}, Elf{index: 2, total: 6, items: 1},
+                    Elf{index: 0, total: 3, items: 2}], result);
+    assert!(Elves::new("1\nx\n".as_bytes()).next().unwrap().is_err());
   }
 
   const INPUT: &str = "1000\n2000\n3000\n\n\
@@ -42,6 +136,20 @@ mod tests {
                        7000\n8000\n9000\n\n\
                        10000";
 
+  #[test]
+  fn test_top_k() {
+    let elves = Elves::new(INPUT.as_bytes()).map(|elf| elf.unwrap());
+    assert_eq!(vec![Elf{index: 3, total: 24000, items: 3},
+                    Elf{index: 2, total: 11000, items: 2}],
+               top_k(elves, 2));
+    let tied = [Elf{index: 0, total: 5, items: 1}, Elf{index: 1, total: 7, items: 1},
+                Elf{index: 2, total: 5, items: 2}];
+    assert_eq!(vec![1, 0, 2], top_k(tied, 5).iter().map(|e| e.index).collect::<Vec<_>>());
+    assert!(top_k(tied, 0).is_empty());
+    assert_eq!(3, top_k(tied, usize::MAX).len());
+    assert_eq!("elf 1 carries 7 calories in 1 items", tied[1].to_string());
+  }
+
   #[test]
   fn test_part1() {
     assert_eq!(24000, part1(&generator(INPUT)));
@@ -50,5 +158,9 @@ mod tests {
   #[test]
   fn test_part2() {
     assert_eq!(45000, part2(&generator(INPUT)));
+    params::install(BTreeMap::from([("day1.top".to_string(), "2".to_string())]));
+    assert_eq!(2, generator(INPUT).len());
+    assert_eq!(35000, part2(&generator(INPUT)));
+    params::uninstall();
   }
 }
@@ -1,6 +1,10 @@
+use serde::Serialize;
+use crate::utils::Answer;
+use crate::utils::simulation::Simulation;
+
 type InputType = Vec<Command>;
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub enum Command {
   AddX(i64),
   NoOp,
@@ -43,6 +47,55 @@ impl State {
   }
 }
 
+/// The CPU running one cycle at a time.
+pub struct Cpu<'a> {
+  program: &'a [Command],
+  /// The instruction being executed
+  pc: usize,
+  /// The number of cycles that are finished
+  cycle: usize,
+  x: i64,
+  /// Has the current addx already used its first cycle?
+  busy: bool,
+}
+
+impl<'a> Cpu<'a> {
+  pub fn new(input: &'a InputType) -> Self {
+    Cpu{program: input, pc: 0, cycle: 0, x: 1, busy: false}
+  }
+}
+
+impl Simulation for Cpu<'_> {
+  fn step(&mut self) {
+    self.cycle += 1;
+    match self.program[self.pc] {
+      Command::AddX(_) if !self.busy => self.busy = true,
+      Command::AddX(val) => {
+        self.x += val;
+        self.busy = false;
+        self.pc += 1;
+      }
+      Command::NoOp => self.pc += 1,
+    }
+  }
+
+  fn is_done(&self) -> bool {
+    self.pc == self.program.len()
+  }
+
+  fn describe(&self) -> String {
+    // The signal strength uses the value of x during the next cycle.
+    let during = self.cycle + 1;
+    let mut result = format!("cycle {during}: x = {}, signal = {}, pixel = '{}'\n", self.x,
+                             self.x * during as i64, pixel(self.x, self.cycle % PERIOD));
+    if let Some(cmd) = self.program.get(self.pc) {
+      let stage = if self.busy { " (second cycle)" } else { "" };
+      result.push_str(&format!("instruction {}: {cmd:?}{stage}\n", self.pc + 1));
+    }
+    result
+  }
+}
+
 const FIRST_CHECK: usize = 20;
 const PERIOD: usize = 40;
 
@@ -68,7 +121,7 @@ fn pixel(value: i64, column: usize) -> char {
   }
 }
 
-pub fn part2(input: &InputType) -> String {
+pub fn part2(input: &InputType) -> Answer {
   let mut result = String::new();
   let mut state = State::default();
   let mut time: usize = 0;
@@ -77,23 +130,39 @@ pub fn part2(input: &InputType) -> String {
     while time < state.time {
       result.push(pixel(state.prev, time % PERIOD));
       time += 1;
-      if time % PERIOD == 0 {
+      if time.is_multiple_of(PERIOD) {
         result.push('\n');
       }
     }
   }
-  result
+  Answer::grid(&result)
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day10::{generator, part1, part2};
+  use crate::day10::{generator, part1, part2, Cpu};
+  use crate::utils::simulation::Simulation;
+  use crate::utils::Answer;
 
   #[test]
   fn test_part1() {
     assert_eq!(13140, part1(&generator(INPUT)));
   }
 
+  #[test]
+  fn test_cpu() {
+    let input = generator(INPUT);
+    let mut cpu = Cpu::new(&input);
+    for _ in 0..19 {
+      cpu.step();
+    }
+    assert!(cpu.describe().starts_with("cycle 20: x = 21, signal = 420,"));
+    for _ in 0..200 {
+      cpu.step();
+    }
+    assert!(cpu.describe().starts_with("cycle 220: x = 18, signal = 3960,"));
+  }
+
   #[test]
   fn test_part2() {
     let expected = "##  ##  ##  ##  ##  ##  ##  ##  ##  ##  \n\
@@ -101,8 +170,8 @@ mod tests {
                            ####    ####    ####    ####    ####    \n\
                            #####     #####     #####     #####     \n\
                            ######      ######      ######      ####\n\
-                           #######       #######       #######     \n".to_string();
-    assert_eq!(expected, part2(&generator(INPUT)));
+                           #######       #######       #######     \n";
+    assert_eq!(Answer::grid(expected), part2(&generator(INPUT)));
   }
 
   const INPUT: &str =
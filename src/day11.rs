@@ -1,11 +1,14 @@
 use std::collections::VecDeque;
+use serde::Serialize;
+use crate::utils::checked;
+use crate::utils::parse::{self, Cursor};
 
 type InputType = Vec<Monkey>;
 type OutputType = usize;
 type ItemType = u64;
 type LiteralType = u64;
 
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize)]
 enum Operation {
   Add(ItemType),
   Multiply(ItemType),
@@ -13,29 +16,25 @@ enum Operation {
 }
 
 impl Operation {
-  fn parse(input: &str) -> Result<Self, String> {
-    match input.split_whitespace().collect::<Vec<&str>>().as_slice()[3..] {
-      ["old", "*", "old"] => Ok(Self::Square),
-      ["old", "+", lit] => Ok(Self::Add(parse_lit(lit)?)),
-      ["old", "*", lit] => Ok(Self::Multiply(parse_lit(lit)?)),
-      _ => Err(format!("Can't parse '{}'", input)),
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Operation: new = old")?;
+    match cursor.one_of(&["+", "*"])? {
+      "*" if cursor.accept("old") => Ok(Self::Square),
+      "*" => Ok(Self::Multiply(cursor.integer()?)),
+      _ => Ok(Self::Add(cursor.integer()?)),
     }
   }
 
   fn perform(&self, val: &mut ItemType) {
     match self {
-      Self::Add(lit) => { *val += lit },
-      Self::Multiply(lit) => { *val *= lit },
-      Self::Square => { *val *= *val },
+      Self::Add(lit) => { *val = checked::add(*val, *lit, "Operation::Add") },
+      Self::Multiply(lit) => { *val = checked::mul(*val, *lit, "Operation::Multiply") },
+      Self::Square => { *val = checked::mul(*val, *val, "Operation::Square") },
     }
   }
 }
 
-fn parse_lit(input: &str) -> Result<ItemType, String> {
-  input.parse::<ItemType>().map_err(|e| format!("{}", e))
-}
-
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize)]
 enum Test {
   Divisble(ItemType),
 }
@@ -43,17 +42,15 @@ enum Test {
 impl Test {
   fn check(&self, val: ItemType) -> bool {
     match self {
-      Self::Divisble(lit) => val % lit == 0
+      Self::Divisble(lit) => val.is_multiple_of(*lit)
     }
   }
 }
 
 impl Test {
-  fn parse(input: &str) -> Result<Self, String> {
-    match input.split_whitespace().collect::<Vec<&str>>().as_slice()[1..] {
-      ["divisible", "by", lit] => Ok(Self::Divisble(parse_lit(lit)?)),
-      _ => Err(format!("Can't parse '{}'", input)),
-    }
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Test: divisible by")?;
+    Ok(Self::Divisble(cursor.integer()?))
   }
 
   fn divisor(&self) -> LiteralType {
@@ -69,7 +66,7 @@ pub struct FlyingObject {
   target: usize,
 }
 
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize)]
 pub struct Monkey {
   items: VecDeque<ItemType>,
   operation: Operation,
@@ -79,22 +76,19 @@ pub struct Monkey {
 }
 
 impl Monkey {
-  fn parse_items(input: &str) -> Result<VecDeque<ItemType>, String> {
-    input.split(", ").map(parse_lit).collect()
-  }
-
-  fn parse_next(input: &str) -> Result<usize, String> {
-    let word = input.split_whitespace().nth(5).ok_or("bad next")?;
-    word.parse().map_err(|_| format!("bad next int '{}'", input))
+  fn parse_next(cursor: &mut Cursor, outcome: &str) -> parse::Result<usize> {
+    cursor.literal(&format!("If {outcome}: throw to monkey"))?;
+    cursor.integer()
   }
 
-  fn parse(input: &str) -> Result<Self, String> {
-    let lines: Vec<&str> = input.lines().collect();
-    let items = Self::parse_items(lines[1].split_once(": ")
-      .ok_or("bad items")?.1)?;
-    let operation = Operation::parse(lines[2])?;
-    let test = Test::parse(lines[3])?;
-    let next = (Self::parse_next(lines[4])?, Self::parse_next(lines[5])?);
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Monkey")?;
+    cursor.integer::<usize>()?;
+    cursor.literal(": Starting items:")?;
+    let items = cursor.separated(",", Cursor::integer)?.into();
+    let operation = Operation::parse(cursor)?;
+    let test = Test::parse(cursor)?;
+    let next = (Self::parse_next(cursor, "true")?, Self::parse_next(cursor, "false")?);
     Ok(Monkey{items, operation, test, next, inspected: 0})
   }
 
@@ -113,12 +107,11 @@ impl Monkey {
 }
 
 pub fn generator(input: &str) -> InputType {
-  input.split("\n\n")
-    .map(|monkey| Monkey::parse(monkey).unwrap())
-    .collect::<Vec<Monkey>>()
+  parse::parse(input, |c| c.repeat(Monkey::parse))
+    .unwrap_or_else(|e| panic!("Can't parse monkeys: {e}"))
 }
 
-fn do_round(monkies: &mut Vec<Monkey>, clip: impl Fn(ItemType) -> ItemType) {
+fn do_round(monkies: &mut [Monkey], clip: impl Fn(ItemType) -> ItemType) {
   for m in 0..monkies.len() {
     while let Some(flying) = monkies[m].next_throw(&clip) {
       monkies[flying.target].catch_object(flying.item);
@@ -135,7 +128,8 @@ fn compute_top_two(monkies: &[Monkey]) -> OutputType {
 fn find_multiple(monkies: &[Monkey], extra_factors: &[ItemType]) -> ItemType {
   let mut factors: Vec<ItemType> = monkies.iter().map(|m| m.test.divisor()).collect();
   factors.extend(extra_factors.iter());
-  factors.iter().fold(1, |acc, v| if acc % v == 0 { acc } else { acc * v })
+  factors.iter().fold(1, |acc, &v|
+    if acc % v == 0 { acc } else { checked::mul(acc, v, "the common multiple") })
 }
 
 pub fn part1(input: &InputType) -> OutputType {
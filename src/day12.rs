@@ -1,20 +1,13 @@
-use std::cmp::Reverse;
-use priority_queue::PriorityQueue;
+use serde::Serialize;
+use crate::utils::grid::{Grid, Position};
+use crate::utils::search::{self, Path, WeightedGraph};
 
 type InputType = Puzzle;
 type OutputType = usize;
 
-#[derive(Clone,Debug,Default,Eq,Hash,Ord,PartialEq,PartialOrd)]
-struct Position {
-  x: usize,
-  y: usize,
-}
-
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Puzzle {
-  elevations: Vec<Vec<u8>>,
-  width: usize,
-  height: usize,
+  elevations: Grid<u8>,
   start: Position,
   end: Position,
 }
@@ -23,40 +16,20 @@ impl Puzzle {
   fn parse(input: &str) -> Self {
     let mut start = Position::default();
     let mut end = Position::default();
-    let mut elevations = Vec::new();
-    for line in input.lines() {
-      let mut row = Vec::new();
-      for ch in line.chars() {
-        match ch {
-          'a'..='z' => { row.push((ch as u8) - b'a'); },
-          'S' => { start = Position{x: row.len(), y: elevations.len()}; row.push(0u8); },
-          'E' => { end = Position{x: row.len(), y: elevations.len()}; row.push(25u8); },
-          _ => panic!("Unknown character {ch}"),
-        }
+    let elevations = Grid::parse_with(input, |pos, ch| {
+      match ch {
+        'a'..='z' => (ch as u8) - b'a',
+        'S' => { start = pos; 0u8 },
+        'E' => { end = pos; 25u8 },
+        _ => panic!("Unknown character {ch}"),
       }
-      elevations.push(row);
-    }
-    let height = elevations.len();
-    let width = if height == 0 { 0 } else { elevations[0].len() };
-    Puzzle{ elevations, width, height, start, end}
+    });
+    Puzzle{ elevations, start, end}
   }
 
-  fn next(&self, pos: &Position) -> Vec<Position> {
-    let mut result = Vec::new();
-    let elevation = self.elevations[pos.y][pos.x] + 1;
-    if pos.x > 0 && self.elevations[pos.y][pos.x - 1] <= elevation {
-      result.push(Position{x: pos.x - 1, y: pos.y})
-    }
-    if pos.x < self.width - 1 && self.elevations[pos.y][pos.x + 1] <= elevation{
-      result.push(Position{x: pos.x + 1, y: pos.y})
-    }
-    if pos.y > 0 && self.elevations[pos.y - 1][pos.x] <= elevation{
-      result.push(Position{x: pos.x, y: pos.y - 1})
-    }
-    if pos.y < self.height - 1 && self.elevations[pos.y + 1][pos.x] <= elevation{
-      result.push(Position{x: pos.x, y: pos.y + 1})
-    }
-    result
+  fn next(&self, pos: Position) -> impl Iterator<Item=Position> + '_ {
+    let elevation = self.elevations[pos] + 1;
+    self.elevations.neighbors4(pos).filter(move |&p| self.elevations[p] <= elevation)
   }
 }
 
@@ -64,40 +37,34 @@ pub fn generator(input: &str) -> InputType {
   Puzzle::parse(input)
 }
 
-fn shortest_distance(input: &Puzzle, starting: Vec<Position>) -> usize {
-  let mut distance = vec!{vec!{usize::MAX; input.width}; input.height};
-  let mut queue = PriorityQueue::new();
-  for start in starting {
-    distance[start.y][start.x] = 0;
-    queue.push(start, Reverse(0));
+/// Each step costs 1, and the Manhattan distance to the end is a lower bound.
+impl WeightedGraph for Puzzle {
+  type Node = Position;
+  type Cost = usize;
+
+  fn edges(&self, pos: &Position) -> impl Iterator<Item=(Position, usize)> {
+    self.next(*pos).map(|p| (p, 1))
   }
-  while let Some((current, Reverse(dist))) = queue.pop() {
-    if current == input.end {
-      return dist
-    }
-    for next in input.next(&current) {
-      if dist + 1 < distance[next.y][next.x] {
-        distance[next.y][next.x] = dist + 1;
-        queue.push(next, Reverse(dist + 1));
-      }
-    }
+
+  fn estimate(&self, pos: &Position) -> usize {
+    pos.0.abs_diff(self.end.0) + pos.1.abs_diff(self.end.1)
   }
-  distance[input.end.y][input.end.x]
+}
+
+fn shortest_distance(input: &Puzzle, starting: Vec<Position>) -> usize {
+  search::astar(input, starting, |&pos| pos == input.end, Path::Skip)
+    .map_or(usize::MAX, |found| found.cost)
 }
 
 pub fn part1(input: &InputType) -> OutputType {
-  shortest_distance(input, vec!{input.start.clone()})
+  shortest_distance(input, vec!{input.start})
 }
 
 pub fn part2(input: &InputType) -> OutputType {
-  let mut starting = Vec::new();
-  for x in 0..input.width {
-    for y in 0..input.height {
-      if input.elevations[y][x] == 0 {
-        starting.push(Position{x, y});
-      }
-    }
-  }
+  let starting = input.elevations.iter()
+    .filter(|(_, &e)| e == 0)
+    .map(|(pos, _)| pos)
+    .collect();
   shortest_distance(input, starting)
 }
 
@@ -3,11 +3,12 @@ use std::fmt::{Display, Formatter};
 use std::iter::Peekable;
 use std::rc::Rc;
 use std::str::Chars;
+use serde::Serialize;
 
 type InputType = Vec<Rc<List>>;
 type OutputType = usize;
 
-#[derive(Clone,Debug,Eq,PartialEq)]
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
 pub enum List {
   Int(i64),
   List(Vec<List>),
@@ -123,7 +124,7 @@ pub fn part1(input: &InputType) -> OutputType {
 
 pub fn part2(input: &InputType) -> OutputType {
   let mut list = input.clone();
-  let dividers = vec![List::parse("[[2]]"), List::parse("[[6]]")];
+  let dividers = [List::parse("[[2]]"), List::parse("[[6]]")];
   list.extend(dividers.iter().cloned());
   list.sort_unstable();
   list.iter().enumerate()
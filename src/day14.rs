@@ -1,17 +1,16 @@
+use serde::Serialize;
+use crate::utils::geom::Point2;
+use crate::utils::grid::Grid;
+use crate::utils::render::{self, Frame};
+
 type InputType = CrossSection;
 type OutputType = usize;
 
-#[derive(Clone,Debug,Eq,PartialEq)]
-struct Point {
-  x: usize,
-  y: usize,
-}
+type Point = Point2<usize>;
 
-impl Point {
-  fn parse(input: &str) -> Self {
-    let (x,y) = input.split_once(',').unwrap();
-    Point{x: x.parse().unwrap(), y: y.parse().unwrap()}
-  }
+fn parse_point(input: &str) -> Point {
+  let (x,y) = input.split_once(',').unwrap();
+  Point::new(x.parse().unwrap(), y.parse().unwrap())
 }
 
 #[derive(Debug)]
@@ -23,7 +22,7 @@ impl Ledges {
   fn parse(input: &str) -> Self {
     let lines = input.lines()
       .map(|s| s.split(" -> ")
-        .map(Point::parse).collect())
+        .map(parse_point).collect())
       .collect();
     Ledges{lines}
   }
@@ -47,9 +46,10 @@ impl Ledges {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct CrossSection {
-  ledges: Vec<Vec<bool>>,
+  /// The filled spots with x offset by left
+  ledges: Grid<bool>,
   left: usize,
   right: usize,
   height: usize,
@@ -60,14 +60,14 @@ impl CrossSection {
     let left = input.left() - 1;
     let right = input.right() + 1;
     let height = input.height() + 1;
-    let mut ledges = vec![vec![false; right - left + 1]; height + 1];
+    let mut ledges = Grid::new(right - left + 1, height + 1, false);
     for row in &input.lines {
       for points in row.windows(2) {
-        let mut p = points[0].clone();
+        let mut p = points[0];
         let delta_x = i64::signum(points[1].x as i64 - points[0].x as i64);
         let delta_y = i64::signum(points[1].y as i64 - points[0].y as i64);
         loop {
-          ledges[p.y][p.x - left] = true;
+          ledges[(p.x - left, p.y)] = true;
           if p == points[1] {
             break
           }
@@ -85,16 +85,16 @@ pub fn generator(input: &str) -> InputType {
   CrossSection::new(&ledges)
 }
 
-fn do_grain(filled: &mut [Vec<bool>], start: &Point, input: &InputType) -> bool {
+fn do_grain(filled: &mut Grid<bool>, start: &Point, input: &InputType) -> bool {
   let mut x = START.x;
-  if filled[start.y][start.x - input.left] {
+  if filled[(start.x - input.left, start.y)] {
     return false
   }
   for y in START.y..input.height {
-    if filled[y+1][x - input.left] {
-      if filled[y+1][x - 1 - input.left] {
-        if filled[y+1][x + 1 - input.left] {
-          filled[y][x - input.left] = true;
+    if filled[(x - input.left, y+1)] {
+      if filled[(x - 1 - input.left, y+1)] {
+        if filled[(x + 1 - input.left, y+1)] {
+          filled[(x - input.left, y)] = true;
           return true
         } else {
           x += 1;
@@ -107,7 +107,13 @@ fn do_grain(filled: &mut [Vec<bool>], start: &Point, input: &InputType) -> bool
   false
 }
 
-const START: Point = Point{x: 500, y: 0};
+const START: Point = Point::new(500, 0);
+
+/// Draw the ledges and the sand that has come to rest.
+fn draw(filled: &Grid<bool>, input: &InputType) -> Frame {
+  Grid::from_fn(filled.width(), filled.height(), |pos|
+    if input.ledges[pos] { '#' } else if filled[pos] { 'o' } else { '.' })
+}
 
 pub fn part1(input: &InputType) -> OutputType {
   let mut filled = input.ledges.clone();
@@ -115,23 +121,23 @@ pub fn part1(input: &InputType) -> OutputType {
     if !do_grain(&mut filled, &START, input) {
       return grain
     }
+    render::frame(|| draw(&filled, input));
   }
   usize::MAX
 }
 
-#[allow(clippy::needless_range_loop)]
 fn add_floor(input: &CrossSection, start: &Point) -> CrossSection {
   let height = input.height + 1;
   let left = usize::min(start.x - height, input.left);
   let right = usize::max(start.x + height, input.right);
-  let mut ledges = vec![vec![false; right - left + 1]; height + 1];
+  let mut ledges = Grid::new(right - left + 1, height + 1, false);
   for x in input.left..=input.right {
     for y in start.y..input.height {
-      ledges[y][x - left] = input.ledges[y][x - input.left];
+      ledges[(x - left, y)] = input.ledges[(x - input.left, y)];
     }
   }
   for x in left..=right {
-    ledges[height][x - left] = true;
+    ledges[(x - left, height)] = true;
   }
   CrossSection{ledges, left, right, height}
 }
@@ -143,6 +149,7 @@ pub fn part2(input: &InputType) -> OutputType {
     if !do_grain(&mut filled, &START, &extended) {
       return grain
     }
+    render::frame(|| draw(&filled, &extended));
   }
   usize::MAX
 }
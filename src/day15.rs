@@ -1,38 +1,38 @@
-use std::cmp::Ordering;
 use std::ops::Range;
+use serde::Serialize;
+use crate::utils::anonymize::{Anonymized, AnswerMap, Rng};
+use crate::utils::Answer;
+use crate::utils::geom::Point2;
+use crate::utils::intervals::IntervalSet;
+use crate::utils::parse::{self, Cursor};
 
 type InputType = Vec<Sensor>;
 type OutputType = usize;
 
-#[derive(Clone,Debug,Eq,PartialEq)]
-struct Point {
-  x: i64,
-  y: i64,
-}
+type Point = Point2<i64>;
 
-impl Point {
-  fn parse(input: &str) -> Self {
-    let (x,y) = input.split_once(", ").unwrap();
-    // skip over the x= or y=
-    Point{x: x[2..].parse().unwrap(), y: y[2..].parse().unwrap()}
-  }
+fn parse_point(cursor: &mut Cursor) -> parse::Result<Point> {
+  let x = cursor.field("x", Cursor::integer)?;
+  cursor.literal(",")?;
+  Ok(Point::new(x, cursor.field("y", Cursor::integer)?))
 }
 
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize)]
 pub struct Sensor {
   location: Point,
   closest: Point,
 }
 
 impl Sensor {
-  fn parse(input: &str) -> Self {
-    let (left, right)= input.split_once(": closest beacon is at ").unwrap();
-    Sensor{location: Point::parse(&left[10..]), closest: Point::parse(right)}
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Sensor at")?;
+    let location = parse_point(cursor)?;
+    cursor.literal(": closest beacon is at")?;
+    Ok(Sensor{location, closest: parse_point(cursor)?})
   }
 
   fn min_distance(&self) -> i64 {
-    i64::abs(self.location.x - self.closest.x) +
-        i64::abs(self.location.y - self.closest.y)
+    self.location.manhattan(&self.closest)
   }
 
   fn invalid_range_on(&self, y: i64) -> Option<Range<i64>> {
@@ -47,7 +47,8 @@ impl Sensor {
 }
 
 pub fn generator(input: &str) -> InputType {
-  input.lines().map(Sensor::parse).collect()
+  parse::parse(input, |c| c.repeat(Sensor::parse))
+    .unwrap_or_else(|e| panic!("Can't parse sensors: {e}"))
 }
 
 /// Find and dedup the list of beacons in a given row
@@ -59,67 +60,23 @@ fn get_beacons_at_row(input: &InputType, y: i64) -> Vec<i64> {
   result
 }
 
-/// Sort and dedup the given ranges.
-fn simplify_ranges(ranges: &mut Vec<Range<i64>>) {
-  ranges.sort_unstable_by(|r1, r2| match (r1.start.cmp(&r2.start), r1.end.cmp(&r2.end)) {
-    (left@(Ordering::Less|Ordering::Greater), _) => left,
-    (Ordering::Equal, right) => right,
-  });
-  let mut i: usize = 0;
-  while i < ranges.len() - 1 {
-    if ranges[i].end > ranges[i+1].start {
-      ranges[i].end = i64::max(ranges[i].end, ranges[i+1].end);
-      ranges.remove(i+1);
-    } else {
-      i += 1;
-    }
-  }
-}
-
-fn ranges_include_location(ranges: &[Range<i64>], x: i64) -> bool {
-  ranges.iter().any(|r| r.start <= x && x < r.end )
-}
-
-/// Count the number of locations covered by the ranges
-fn count_locations(ranges: &[Range<i64>]) -> usize {
-  ranges.iter().map(|r| r.end - r.start).sum::<i64>() as usize
-}
-
 /// Count how many spots can't have beacons at them in the given row.
 fn get_unavailable_at_row(input: &InputType, y: i64) -> OutputType {
-  let mut ranges: Vec<Range<i64>> = input.iter().filter_map(|s| s.invalid_range_on(y)).collect();
-  simplify_ranges(&mut ranges);
+  let covered: IntervalSet<i64> = input.iter().filter_map(|s| s.invalid_range_on(y)).collect();
   // Find the beacon locations that would otherwise count
   let mut beacons = get_beacons_at_row(input, y);
-  beacons.retain(|x| ranges_include_location(&ranges, *x));
-  count_locations(&ranges) - beacons.len()
-}
-
-pub fn part1(input: &InputType) -> OutputType {
-  get_unavailable_at_row(input, 2_000_000)
+  beacons.retain(|x| covered.contains(x));
+  covered.len() as usize - beacons.len()
 }
 
-/// Points that are rotated by 45 degrees
-#[derive(Clone,Debug)]
-struct SlantPoint {
-  diag_x: i64,
-  diag_y: i64,
-}
-
-impl SlantPoint {
-  fn from(pt: &Point) -> Self {
-    SlantPoint{diag_x: pt.x + pt.y, diag_y: pt.y - pt.x}
-  }
+/// The row that part 1 checks, which is half of PART2_LIMIT.
+const PART1_ROW: i64 = 2_000_000;
 
-  fn point(&self) -> Point {
-    Point{x: (self.diag_x - self.diag_y)/2, y: (self.diag_x + self.diag_y)/2}
-  }
-
-  fn is_valid(&self) -> bool {
-    (self.diag_x % 2 == 0) == (self.diag_y % 2 == 0)
-  }
+pub fn part1(input: &InputType) -> OutputType {
+  get_unavailable_at_row(input, PART1_ROW)
 }
 
+/// A box in the coordinates that are rotated by 45 degrees
 #[derive(Clone,Debug)]
 struct SlantBox {
   left: i64,
@@ -131,9 +88,9 @@ struct SlantBox {
 impl SlantBox {
   fn from(sensor: &Sensor) -> Self {
     let dist = sensor.min_distance();
-    let mid = SlantPoint::from(&sensor.location);
-    SlantBox{bottom: mid.diag_y - dist, top: mid.diag_y + dist + 1,
-      left: mid.diag_x - dist, right: mid.diag_x + dist + 1}
+    let mid = sensor.location.rotate45();
+    SlantBox{bottom: mid.y - dist, top: mid.y + dist + 1,
+      left: mid.x - dist, right: mid.x + dist + 1}
   }
 }
 
@@ -172,9 +129,7 @@ fn find_sensor(input: &InputType, x_range: Range<i64>, y_range: Range<i64>) -> P
   for y in 0..valid.len(){
     for x in 0..valid[y].len() {
       if valid[y][x] {
-        let slant = SlantPoint{diag_x: x_div[x], diag_y: y_div[y]};
-        if slant.is_valid() {
-          let pt = slant.point();
+        if let Some(pt) = Point::new(x_div[x], y_div[y]).unrotate45() {
           if x_range.contains(&pt.x) && y_range.contains(&pt.y) {
             return pt;
           }
@@ -182,7 +137,7 @@ fn find_sensor(input: &InputType, x_range: Range<i64>, y_range: Range<i64>) -> P
       }
     }
   }
-  Point{x:0, y:0}
+  Point::default()
 }
 
 const PART2_LIMIT: i64 = 4_000_000;
@@ -192,9 +147,40 @@ pub fn part2(input: &InputType) -> OutputType {
   (pt.x * PART2_LIMIT + pt.y) as usize
 }
 
+/// Reflect the sensors within 0..=limit across the middle in x and maybe y,
+/// and shuffle them. The middle row stays the same and the distress beacon
+/// is reflected, so its tuning frequency changes in a known way.
+fn reflect(input: &str, limit: i64, rng: &mut Rng) -> Anonymized {
+  let flip_y = rng.coin();
+  let reflect = move |p: &Point| Point::new(limit - p.x, if flip_y { limit - p.y } else { p.y });
+  let mut sensors = generator(input);
+  rng.shuffle(&mut sensors);
+  let text = sensors.iter().map(|s| {
+    let (location, closest) = (reflect(&s.location), reflect(&s.closest));
+    format!("Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+            location.x, location.y, closest.x, closest.y)
+  }).collect();
+  let answers = AnswerMap::Map(Box::new(move |part, answer| match (part, answer) {
+    (1, Answer::Integer(freq)) => {
+      let limit = limit as i128;
+      let (x, y) = (freq / limit, freq % limit);
+      let point = reflect(&Point::new(x as i64, y as i64));
+      Answer::Integer(point.x as i128 * limit + point.y as i128)
+    },
+    _ => answer.clone(),
+  }));
+  Anonymized{text, answers}
+}
+
+pub fn anonymize(input: &str, rng: &mut Rng) -> Anonymized {
+  reflect(input, PART2_LIMIT, rng)
+}
+
 #[cfg(test)]
 mod tests {
-  use crate::day15::{find_sensor, generator, get_unavailable_at_row, Point};
+  use crate::day15::{find_sensor, generator, get_unavailable_at_row, reflect, Point};
+  use crate::utils::Answer;
+  use crate::utils::anonymize::Rng;
 
 
   #[test]
@@ -205,10 +191,23 @@ mod tests {
 
   #[test]
   fn test_part2() {
-    assert_eq!(Point{x: 14, y:11},
+    assert_eq!(Point::new(14, 11),
                find_sensor(&generator(INPUT), 0..21, 0..21));
   }
 
+  #[test]
+  fn test_reflect() {
+    for seed in 0..4 {
+      let anonymized = reflect(INPUT, 20, &mut Rng::new(seed));
+      let sensors = generator(&anonymized.text);
+      assert_eq!(26, get_unavailable_at_row(&sensors, 10));
+      let expected = anonymized.answers.apply(&[Answer::Integer(26), Answer::Integer(14 * 20 + 11)])
+        .unwrap();
+      let point = find_sensor(&sensors, 0..21, 0..21);
+      assert_eq!(expected[1], Answer::Integer((point.x * 20 + point.y) as i128));
+    }
+  }
+
   const INPUT: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
                        Sensor at x=9, y=16: closest beacon is at x=10, y=16\n\
                        Sensor at x=13, y=2: closest beacon is at x=15, y=3\n\
@@ -1,4 +1,10 @@
 use std::collections::HashMap;
+use serde::Serialize;
+use crate::utils::anonymize::{self, Anonymized, AnswerMap, Rng};
+use crate::utils::bitset::GrowableBitSet;
+use crate::utils::checked;
+use crate::utils::parse::{self, Cursor};
+use crate::utils::search::{self, Bounded};
 
 type InputType = Caves;
 type OutputType = u64;
@@ -15,32 +21,35 @@ pub struct Valve {
 }
 
 impl Valve {
-  fn parse(line: &str) -> Self {
-    let words: Vec<&str> = line.split_whitespace().collect();
-    let name = words[1].to_string();
-    // remove "rate=" and ";"
-    let mut flow_str = words[4][5..].to_string();
-    flow_str.pop();
-    let flow = flow_str.parse::<u64>().unwrap();
-    let next: Vec<String> = words[9..].join(" ").split(", ").map(|s| s.to_string()).collect();
-    Valve{name, flow, next}
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Valve")?;
+    let name = cursor.word()?.to_string();
+    cursor.literal("has flow")?;
+    let flow = cursor.field("rate", Cursor::integer)?;
+    cursor.literal(";")?;
+    cursor.one_of(&["tunnels lead to valves", "tunnel leads to valve"])?;
+    let next = cursor.separated(",", |c| c.word().map(|w| w.to_string()))?;
+    Ok(Valve{name, flow, next})
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Caves {
   start: usize,
   flows: Vec<u64>,
   /// Distance[from][dest]
-  distances: Vec<Vec<u8>>,
+  distances: Vec<Vec<u16>>,
 }
 
 impl Caves {
   fn parse(input: &str) -> Self {
-    let valves: Vec<Valve> = input.lines().map(Valve::parse).collect();
+    let valves: Vec<Valve> = parse::parse(input, |c| c.repeat(Valve::parse))
+      .unwrap_or_else(|e| panic!("Can't parse valves: {e}"));
     let mut map: HashMap<&str, usize> = HashMap::new();
     let mut flows: Vec<u64> = vec![0; valves.len()];
-    let mut distances = vec![vec![valves.len() as u8; valves.len()]; valves.len()];
+    // no path is longer than the number of valves, so use it as infinity
+    let infinity: u16 = checked::from_usize(valves.len(), "the valve count");
+    let mut distances = vec![vec![infinity; valves.len()]; valves.len()];
     for (i, v) in valves.iter().enumerate() {
       map.insert(&v.name, i);
       flows[i] = v.flow;
@@ -57,26 +66,25 @@ impl Caves {
     for i in 0..distances.len() {
       for j in 0..distances.len() {
         for k in 0..distances.len() {
-          distances[j][k] = u8::min(distances[j][k], distances[j][i] + distances[i][k]);
+          distances[j][k] = u16::min(distances[j][k], checked::add(distances[j][i],
+            distances[i][k], "the distance sum"));
         }
       }
     }
     Caves{start, flows, distances}
   }
 
-  /// Get the u64/bitmap of the closed valves
-  fn get_closed(&self) -> u64 {
+  /// Get the set of the closed valves that have flow
+  fn get_closed(&self) -> GrowableBitSet {
     self.flows.iter().enumerate()
       .filter(|(_, &f)| f > 0)
-      .fold(0, |acc, (i, _)| acc | (1 << i))
+      .map(|(i, _)| i)
+      .collect()
   }
 
   /// Get the sum of the remaining flows.
-  fn get_available_flow(&self, shut: u64) -> u64 {
-    self.flows.iter().enumerate()
-      .filter(|&(i, &f)| f > 0 && shut & (1 << i) != 0)
-      .map(|(_, &f)| f)
-      .sum()
+  fn get_available_flow(&self, shut: &GrowableBitSet) -> u64 {
+    shut.iter().map(|i| self.flows[i]).sum()
   }
 }
 
@@ -84,23 +92,10 @@ pub fn generator(input: &str) -> InputType {
   Caves::parse(input)
 }
 
-/// Define the interface for this problem's state types.
-trait State {
-  /// The type itself, so that we don't need to box the results
-  /// of next.
-  type Data: State;
-  /// Which states are reachable from this one?
-  fn next(&self, caves: &Caves) -> Vec<Self::Data>;
-  /// What is the score for this state?
-  fn score(&self) -> u64;
-  /// What is the estimated upper bound on this state?
-  fn upper_bound(&self, caves: &Caves) -> u64;
-}
-
 #[derive(Clone,Debug)]
 pub struct Part1 {
-  /// bit map of the valves that are still closed
-  shut: u64,
+  /// the valves that are still closed
+  shut: GrowableBitSet,
   location: usize,
   remaining_time: u64,
   total_flow: u64,
@@ -117,25 +112,24 @@ impl Part1 {
   }
 
   fn move_to(&self, dest: usize, caves: &Caves) -> Self {
-    let shut = self.shut & !(1 << dest);
+    let mut shut = self.shut.clone();
+    shut.remove(dest);
     let remaining_time = self.remaining_time - caves.distances[self.location][dest] as u64 - 1;
     let total_flow = self.total_flow + remaining_time * caves.flows[dest];
     Part1 { location: dest, shut, remaining_time, total_flow }
   }
 }
 
-impl State for Part1 {
-  type Data = Part1;
+impl Bounded for Part1 {
+  type Context = Caves;
+  type Score = u64;
 
-  fn next(&self, caves: &Caves) -> Vec<Self> {
+  fn branches(&self, caves: &Caves) -> Vec<Self> {
     let mut result = Vec::new();
-    let mut closed_valves = self.shut;
-    while closed_valves != 0 {
-      let zeros = closed_valves.trailing_zeros() as usize;
-      if caves.distances[self.location][zeros] as u64 + 1 < self.remaining_time {
-        result.push(self.move_to(zeros, caves));
+    for valve in self.shut.iter() {
+      if caves.distances[self.location][valve] as u64 + 1 < self.remaining_time {
+        result.push(self.move_to(valve, caves));
       }
-      closed_valves &= !(1 << zeros);
     }
     result
   }
@@ -147,39 +141,23 @@ impl State for Part1 {
   fn upper_bound(&self, caves: &Caves) -> u64 {
     let remaining_time = self.remaining_time;
     if remaining_time > 1 {
-      self.total_flow + (remaining_time - 1) * caves.get_available_flow(self.shut)
+      self.total_flow + (remaining_time - 1) * caves.get_available_flow(&self.shut)
     } else {
       self.total_flow
     }
   }
 }
 
-/// Generic routine that searches for the highest score from
-/// the given initial state.
-fn search<T: State<Data=T>>(caves: &Caves, initial: T) -> OutputType {
-  let mut queue = Vec::new();
-  queue.push(initial);
-  let mut max = 0;
-  while let Some(state) = queue.pop() {
-    max = max.max(state.score());
-    let next: Vec<T> = state.next(caves);
-    if !next.is_empty() {
-      queue.extend(next.into_iter().filter(|s| s.upper_bound(caves) > max));
-    }
-  }
-  max
-}
-
 pub fn part1(input: &InputType) -> OutputType {
-  search(input, Part1::new(input))
+  search::maximize(input, Part1::new(input)).score()
 }
 
 /// Now we have 2 workers (us and the elephant), so we
 /// need to track both.
 #[derive(Clone,Debug)]
 struct Part2 {
-  /// bit map of the valves that are still closed
-  shut: u64,
+  /// the valves that are still closed
+  shut: GrowableBitSet,
   locations: [usize; PART2_WORKERS],
   remaining_times: [u64; PART2_WORKERS],
   total_flow: u64,
@@ -196,7 +174,8 @@ impl Part2 {
   }
 
   fn move_to(&self, worker: usize, dest: usize, caves: &Caves) -> Self {
-    let shut = self.shut & !(1 << dest);
+    let mut shut = self.shut.clone();
+    shut.remove(dest);
     let mut locations = self.locations;
     locations[worker] = dest;
     let mut remaining_times = self.remaining_times;
@@ -206,21 +185,19 @@ impl Part2 {
   }
 }
 
-impl State for Part2 {
-  type Data = Part2;
+impl Bounded for Part2 {
+  type Context = Caves;
+  type Score = u64;
 
-  fn next(&self, caves: &Caves) -> Vec<Self> {
+  fn branches(&self, caves: &Caves) -> Vec<Self> {
     let mut result = Vec::new();
     let worker = self.remaining_times.iter().enumerate()
       .fold((0, 0),
             |acc, (i, &v)| if v > acc.1 { (i, v) } else { acc }).0;
-    let mut closed_valves = self.shut;
-    while closed_valves != 0 {
-      let zeros = closed_valves.trailing_zeros() as usize;
-      if caves.distances[self.locations[worker]][zeros] as u64 + 1 < self.remaining_times[worker] {
-        result.push(self.move_to(worker, zeros, caves));
+    for valve in self.shut.iter() {
+      if caves.distances[self.locations[worker]][valve] as u64 + 1 < self.remaining_times[worker] {
+        result.push(self.move_to(worker, valve, caves));
       }
-      closed_valves &= !(1 << zeros);
     }
     result
   }
@@ -232,7 +209,7 @@ impl State for Part2 {
   fn upper_bound(&self, caves: &Caves) -> u64 {
     let remaining_time = *self.remaining_times.iter().min().unwrap();
     if remaining_time > 1 {
-      self.total_flow + (remaining_time - 1) * caves.get_available_flow(self.shut)
+      self.total_flow + (remaining_time - 1) * caves.get_available_flow(&self.shut)
     } else {
       self.total_flow
     }
@@ -240,12 +217,22 @@ impl State for Part2 {
 }
 
 pub fn part2(input: &InputType) -> OutputType {
-  search(input, Part2::new(input))
+  search::maximize(input, Part2::new(input)).score()
+}
+
+/// Rename every valve except the starting one and shuffle the lines.
+pub fn anonymize(input: &str, rng: &mut Rng) -> Anonymized {
+  let names = input.split(|ch: char| !ch.is_alphanumeric())
+    .filter(|word| word.len() == 2 && word.chars().all(|ch| ch.is_ascii_uppercase()));
+  let renames = anonymize::rename(names, &["AA"], 'A'..='Z', rng);
+  let text = anonymize::shuffle_lines(&anonymize::replace_words(input, &renames), rng);
+  Anonymized{text, answers: AnswerMap::Same}
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day16::{generator, part1, part2};
+  use crate::day16::{anonymize, generator, part1, part2};
+  use crate::utils::anonymize::Rng;
 
   #[test]
   fn test_part1() {
@@ -257,6 +244,15 @@ mod tests {
     assert_eq!(1707, part2(&generator(INPUT)));
   }
 
+  #[test]
+  fn test_anonymize() {
+    let anonymized = anonymize(INPUT, &mut Rng::new(5));
+    assert_ne!(INPUT, anonymized.text);
+    let input = generator(&anonymized.text);
+    assert_eq!(1651, part1(&input));
+    assert_eq!(1707, part2(&input));
+  }
+
   const INPUT: &str =
 "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
 Valve BB has flow rate=13; tunnels lead to valves CC, AA
@@ -1,9 +1,12 @@
-use std::collections::HashSet;
+use serde::Serialize;
+use crate::utils::bitset::BitSet;
+use crate::utils::cycle;
+use crate::utils::simulation::Simulation;
 
 type InputType = Vec<Wind>;
 type OutputType = usize;
 
-#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
 pub enum Wind {
   Left,
   Right,
@@ -43,7 +46,7 @@ enum PieceKind {
 
 impl PieceKind {
   /// Return the bitmask in the low bits
-  fn shape(&self) -> &[u8] {
+  fn shape(&self) -> &[u64] {
     match self {
       PieceKind::Bar => &[0xf],
       PieceKind::Plus => &[0x2, 0x7, 0x2],
@@ -66,12 +69,15 @@ impl PieceKind {
 const INITIAL_X: usize = 2;
 const BOARD_WIDTH: usize = 7;
 
+/// Each row of the board is a set of the filled columns.
+type Row = BitSet<{ BOARD_WIDTH.div_ceil(64) }>;
+
 #[derive(Debug)]
 struct Piece {
   /// width, height
   size: (usize, usize),
   /// a static list of the mask at each horizontal position.
-  masks: Vec<Vec<u8>>,
+  masks: Vec<Vec<Row>>,
   /// positions from the left wall
   x: usize,
   /// position from the bottom
@@ -80,12 +86,12 @@ struct Piece {
 
 impl Piece {
   fn new(kind: PieceKind) -> Self {
-    let mut masks: Vec<Vec<u8>> = Vec::new();
+    let mut masks: Vec<Vec<Row>> = Vec::new();
     let size = kind.size();
     let base_mask = kind.shape();
     // Generate each shifted mask
     for x in 0..=(BOARD_WIDTH-size.0) {
-      masks.push(base_mask.iter().map(|&v| v << x).collect());
+      masks.push(base_mask.iter().map(|&v| Row::from(v) << x).collect());
     }
     Piece{size, masks, x:0, y:0}
   }
@@ -95,23 +101,28 @@ impl Piece {
   }
 
   /// Does this piece overlap the given part of the board
-  fn not_blocked(&self, x: usize, board: &[u8]) -> bool {
-    !self.masks[x].iter().zip(board.iter()).any(|(&l,&r) | l & r != 0)
+  fn not_blocked(&self, x: usize, board: &[Row]) -> bool {
+    self.masks[x].iter().zip(board.iter()).all(|(l, r)| l.is_disjoint(r))
   }
 }
 
 const NEW_PIECE_HEIGHT: usize = 3;
 
 struct Board {
-  /// bitmap of the
-  filled: Vec<u8>,
+  /// the filled spots in each row
+  filled: Vec<Row>,
   /// The level of the first empty row
   current_height: usize,
 }
 
 impl Board {
-  fn new(height: usize) -> Self {
-    Board{filled: vec![0; height], current_height: 0}
+  fn new() -> Self {
+    Board{filled: Vec::new(), current_height: 0}
+  }
+
+  /// Make sure there are enough empty rows above the stack to hold a piece.
+  fn make_room(&mut self, piece: &Piece) {
+    self.filled.resize(self.current_height + piece.size.1, Row::new());
   }
 
   /// If the piece can be blown in the given direction, do so.
@@ -138,20 +149,6 @@ impl Board {
   }
 }
 
-/*
-impl Display for Board {
-  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-     for row in (0..self.current_height).rev() {
-       for col in 0..BOARD_WIDTH {
-         write!(f, "{}", if (self.filled[row] >> col) & 1 == 1 {'#'} else {'.'});
-       }
-       write!(f, "\n");
-     }
-    write!(f, "\n")
-  }
-}
-*/
-
 struct WindIter<'a> {
   data: &'a[Wind],
   posn: usize,
@@ -173,100 +170,120 @@ impl<'a> WindIter<'a> {
   }
 }
 
-#[derive(Debug)]
-struct CycleInformation {
-  kind: usize,
-  wind: usize,
-  start: usize,
-  height: usize,
-}
+/// How many of the top rows are part of the cycle detection key.
+const PROFILE_ROWS: usize = 64;
 
-#[derive(Debug)]
-struct TailInformation {
-  remaining_pieces: usize,
-  extra_rows: usize,
+/// The top rows of the board packed together, so the key is cheap to hash.
+type Profile = BitSet<{ (PROFILE_ROWS * BOARD_WIDTH).div_ceil(64) }>;
+
+/// The state of dropping the rocks one at a time.
+pub struct Tower<'a> {
+  board: Board,
+  wind: WindIter<'a>,
+  pieces: Vec<Piece>,
+  dropped: usize,
 }
 
-pub fn drop_rocks(input: &InputType, count: usize) -> OutputType {
-  let mut seen: HashSet<(usize,usize)> = HashSet::new();
-  let mut cycle: Option<CycleInformation> = None;
-  let mut tail: Option<TailInformation> = None;
-  let mut board = Board::new(500_000);
-  let mut wind_itr = WindIter::new(input);
-  let mut pieces: Vec<Piece> = [PieceKind::Bar, PieceKind::Plus, PieceKind::L,
-    PieceKind::I, PieceKind::Square].iter().map(|k| Piece::new(*k)).collect();
-  let num_pieces = pieces.len();
-  for piece_count in 0..count {
-    // Check for cycles in the piece kind and wind
-    let kind_and_wind = (piece_count % num_pieces, wind_itr.get_posn());
-    // Are we computing the tail?
-    if let Some(info) = &mut tail {
-      // just count down the remaining rows
-      if info.remaining_pieces == 0 {
-        break;
-      } else {
-        info.remaining_pieces -= 1;
-      }
-    } else if let Some(info) = &cycle {
-      // Check to see if we have found the second iteration of the cycle. If so,
-      // compute the length of the cycle and how high our virtual stack needs to be.
-      if (info.kind, info.wind) == kind_and_wind {
-        let cycle_time = piece_count - info.start;
-        let cycle_height = board.current_height - info.height;
-        let remaining = count - piece_count;
-        // Set the tail information so that we handle the last partial cycle
-        tail = Some(TailInformation{remaining_pieces: (remaining - 1) % cycle_time,
-          extra_rows: (remaining / cycle_time) * cycle_height});
-        if remaining % cycle_time == 0 {
-          break
-        }
-      }
-    } else {
-      // Look for a repeat of kind and wind to identify the cycle.
-      // The first cycle is usually different, but the second and later cycles are
-      // identical. After the first, we set the cycle information and use that to
-      // identify the second iteration.
-      if seen.contains(&kind_and_wind) {
-        seen.clear();
-        cycle = Some(CycleInformation{kind: kind_and_wind.0, wind: kind_and_wind.1,
-          start: piece_count, height: board.current_height});
-      } else {
-        seen.insert(kind_and_wind);
-      }
+impl<'a> Tower<'a> {
+  fn new(input: &'a InputType) -> Self {
+    let pieces = [PieceKind::Bar, PieceKind::Plus, PieceKind::L,
+      PieceKind::I, PieceKind::Square].iter().map(|k| Piece::new(*k)).collect();
+    Tower{board: Board::new(), wind: WindIter::new(input), pieces, dropped: 0}
+  }
+
+  /// The next piece, the position in the wind, and the shape of the top of
+  /// the stack determine how the following rocks will fall.
+  fn key(&self) -> (usize, usize, Profile) {
+    let mut profile = Profile::new();
+    let height = self.board.current_height;
+    let rows = height.min(PROFILE_ROWS);
+    for (i, row) in self.board.filled[height - rows..height].iter().enumerate() {
+      profile.extend(row.iter().map(|col| i * BOARD_WIDTH + col));
     }
-    let piece = &mut pieces[piece_count % num_pieces];
+    (self.dropped % self.pieces.len(), self.wind.get_posn(), profile)
+  }
+
+  fn drop_rock(&mut self) {
+    let num_pieces = self.pieces.len();
+    let piece = &mut self.pieces[self.dropped % num_pieces];
     // simulate the initial 3 level fall
-    piece.reset(board.current_height);
+    piece.reset(self.board.current_height);
+    self.board.make_room(piece);
     for _ in 0..=NEW_PIECE_HEIGHT {
-      let wind = wind_itr.next();
+      let wind = self.wind.next();
       if let Some(new_x) = wind.blow(piece.x, BOARD_WIDTH + 1 - piece.size.0) {
         piece.x = new_x;
       }
     }
     // now check
-    while board.can_fall(piece) {
+    while self.board.can_fall(piece) {
       piece.y -= 1;
-      board.blow(piece, wind_itr.next());
+      self.board.blow(piece, self.wind.next());
     }
-    board.place_piece(piece);
+    self.board.place_piece(piece);
+    self.dropped += 1;
+  }
+}
+
+/// How many rows at the top of the stack are shown.
+const SHOWN_ROWS: usize = 20;
+
+/// Drop the given number of rocks one at a time.
+pub struct RockDrops<'a> {
+  tower: Tower<'a>,
+  count: usize,
+}
+
+impl<'a> RockDrops<'a> {
+  pub fn new(input: &'a InputType, part: usize) -> Self {
+    RockDrops{tower: Tower::new(input), count: if part == 2 { PART2_ROCKS } else { PART1_ROCKS }}
+  }
+}
+
+impl Simulation for RockDrops<'_> {
+  fn step(&mut self) {
+    self.tower.drop_rock();
+  }
+
+  fn is_done(&self) -> bool {
+    self.tower.dropped == self.count
   }
-  if let Some(info) = tail {
-    board.current_height += info.extra_rows;
+
+  fn describe(&self) -> String {
+    let board = &self.tower.board;
+    let mut result = format!("rocks: {}, height: {}, wind: {}\n", self.tower.dropped,
+                             board.current_height, self.tower.wind.get_posn());
+    let height = board.current_height;
+    for row in board.filled[height.saturating_sub(SHOWN_ROWS)..height].iter().rev() {
+      let cells: String = (0..BOARD_WIDTH).map(|col| if row.contains(col) {'#'} else {'.'})
+        .collect();
+      result.push_str(&format!("|{cells}|\n"));
+    }
+    result
   }
-  board.current_height
+}
+
+const PART1_ROCKS: usize = 2_022;
+const PART2_ROCKS: usize = 1_000_000_000_000;
+
+pub fn drop_rocks(input: &InputType, count: usize) -> OutputType {
+  let history = cycle::find(Tower::new(input), Tower::key,
+                            Tower::drop_rock, |s| s.board.current_height, count);
+  history.extrapolate(count).expect("no cycle found")
 }
 
 pub fn part1(input: &InputType) -> OutputType {
-  drop_rocks(input, 2_022)
+  drop_rocks(input, PART1_ROCKS)
 }
 
 pub fn part2(input: &InputType) -> OutputType {
-  drop_rocks(input, 1_000_000_000_000)
+  drop_rocks(input, PART2_ROCKS)
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day17::{generator, part1, part2};
+  use crate::day17::{generator, part1, part2, RockDrops};
+  use crate::utils::simulation::Simulation;
 
   #[test]
   fn test_part1() {
@@ -275,6 +292,16 @@ mod tests {
     assert_eq!(3068, part1(&input));
   }
 
+  #[test]
+  fn test_rock_drops() {
+    let input = generator(INPUT);
+    let mut drops = RockDrops::new(&input, 1);
+    drops.step();
+    drops.step();
+    assert_eq!("rocks: 2, height: 4, wind: 8\n\
+                |...#...|\n|..###..|\n|...#...|\n|..####.|\n", drops.describe());
+  }
+
   #[test]
   fn test_part2() {
     assert_eq!(1514285714288, part2(&generator(INPUT)));
@@ -1,24 +1,18 @@
 use std::ops::Range;
+use crate::utils::anonymize::{Anonymized, AnswerMap, Rng};
+use crate::utils::geom::Point3;
 
 type InputType = Vec<Point>;
 type OutputType = usize;
 
 type Coordinate = i32;
+type Point = Point3<Coordinate>;
 
-#[derive(Clone,Debug)]
-pub struct Point {
-  x: Coordinate,
-  y: Coordinate,
-  z: Coordinate,
-}
-
-impl Point {
-  fn parse(line: &str) -> Self {
-    let nums: Vec<Coordinate> = line.split(',')
-      .map(|w| w.parse::<Coordinate>().unwrap())
-      .collect();
-    Point{x: nums[0], y: nums[1], z: nums[2]}
-  }
+fn parse_point(line: &str) -> Point {
+  let nums: Vec<Coordinate> = line.split(',')
+    .map(|w| w.parse::<Coordinate>().unwrap())
+    .collect();
+  Point::new(nums[0], nums[1], nums[2])
 }
 
 fn find_range(input: &[Coordinate]) -> Option<Range<Coordinate>> {
@@ -33,7 +27,7 @@ fn find_range(input: &[Coordinate]) -> Option<Range<Coordinate>> {
 }
 
 pub fn generator(input: &str) -> InputType {
-  input.lines().map(Point::parse).collect()
+  input.lines().map(parse_point).collect()
 }
 
 fn size(input: &Range<Coordinate>) -> usize {
@@ -77,19 +71,16 @@ impl Blob {
       [(point.z - self.ranges[2].start) as usize]
   }
 
+  fn contains(&self, point: &Point) -> bool {
+    self.ranges[0].contains(&point.x) &&
+      self.ranges[1].contains(&point.y) &&
+      self.ranges[2].contains(&point.z)
+  }
+
   fn get_neighbors(&self, point: &Point) -> usize {
-    let mut result = 0;
-    for delta in [(1, 0, 0), (-1, 0, 0), (0, 1, 0),
-        (0, -1, 0), (0, 0, 1), (0, 0, -1)] {
-      let other = Point{x: point.x + delta.0, y: point.y + delta.1, z: point.z + delta.2};
-      if self.ranges[0].contains(&other.x) &&
-         self.ranges[1].contains(&other.y) &&
-         self.ranges[2].contains(&other.z) &&
-         self.get(&other) == Kind::Rock {
-        result += 1;
-      }
-    }
-    result
+    point.neighbors6().iter()
+      .filter(|other| self.contains(other) && self.get(other) == Kind::Rock)
+      .count()
   }
 
   fn get_slice(&self, x_range: &Range<Coordinate>, y_range: &Range<Coordinate>,
@@ -98,7 +89,7 @@ impl Blob {
     for x in x_range.clone() {
       for y in y_range.clone() {
         for z in z_range.clone() {
-          result.push(Point{x,y,z});
+          result.push(Point::new(x, y, z));
         }
       }
     }
@@ -147,7 +138,7 @@ pub fn part2(input: &InputType) -> OutputType {
   for pt in &blob.get_box_edges() {
     match blob.get(pt) {
       Kind::Rock => { exterior_faces += 1; },
-      Kind::Air => { pending.push(pt.clone()); }
+      Kind::Air => { pending.push(*pt); }
       Kind::Outside => { }
     }
   }
@@ -157,10 +148,8 @@ pub fn part2(input: &InputType) -> OutputType {
       continue;
     }
     blob.set(&pt, Kind::Outside);
-    for delta in [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)] {
-      let other = Point { x: pt.x + delta.0, y: pt.y + delta.1, z: pt.z + delta.2 };
-      if blob.ranges[0].contains(&other.x) && blob.ranges[1].contains(&other.y) &&
-          blob.ranges[2].contains(&other.z) {
+    for other in pt.neighbors6() {
+      if blob.contains(&other) {
         match blob.get(&other) {
           Kind::Rock => { exterior_faces += 1; },
           Kind::Outside => {},
@@ -172,9 +161,26 @@ pub fn part2(input: &InputType) -> OutputType {
   exterior_faces
 }
 
+/// Permute the axes, move the droplet, and shuffle the cubes. Neither the
+/// surface area nor the outside of the droplet changes.
+pub fn anonymize(input: &str, rng: &mut Rng) -> Anonymized {
+  let mut axes = [0, 1, 2];
+  rng.shuffle(&mut axes);
+  let offset: Vec<Coordinate> = (0..3).map(|_| rng.between(-50..=50) as Coordinate).collect();
+  let mut cubes = generator(input);
+  rng.shuffle(&mut cubes);
+  let text = cubes.iter().map(|cube| {
+    let coords = [cube.x, cube.y, cube.z];
+    format!("{},{},{}\n", coords[axes[0]] + offset[0], coords[axes[1]] + offset[1],
+            coords[axes[2]] + offset[2])
+  }).collect();
+  Anonymized{text, answers: AnswerMap::Same}
+}
+
 #[cfg(test)]
 mod tests {
-  use crate::day18::{generator, part1, part2};
+  use crate::day18::{anonymize, generator, part1, part2};
+  use crate::utils::anonymize::Rng;
 
   #[test]
   fn test_part1() {
@@ -188,6 +194,14 @@ mod tests {
     assert_eq!(58, part2(&generator(INPUT)));
   }
 
+  #[test]
+  fn test_anonymize() {
+    let anonymized = anonymize(INPUT, &mut Rng::new(2));
+    let input = generator(&anonymized.text);
+    assert_eq!(64, part1(&input));
+    assert_eq!(58, part2(&input));
+  }
+
   const INPUT: &str =
 "2,2,2
 1,2,2
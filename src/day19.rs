@@ -1,5 +1,9 @@
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
+use serde::Serialize;
+use crate::utils::parse::{self, Cursor};
+use crate::utils::progress;
+use crate::utils::search::{self, Bounded};
 
 type InputType = Vec<Blueprint>;
 type OutputType = usize;
@@ -18,15 +22,21 @@ enum Resource {
 
 impl Resource {
   const SIZE: usize = 4;
+  const NAMES: [&'static str; Resource::SIZE] = ["ore", "clay", "obsidian", "geode"];
 
   fn idx(&self) -> usize {
     *self as usize
   }
+
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    let name = cursor.one_of(&Self::NAMES)?;
+    Ok(Resource::iter().find(|r| Self::NAMES[r.idx()] == name).unwrap())
+  }
 }
 
 type Count = u16;
 
-#[derive(Debug,Default)]
+#[derive(Debug,Default,Serialize)]
 pub struct Blueprint {
   id: usize,
   robot: [[Count; Resource::SIZE]; Resource::SIZE],
@@ -34,27 +44,34 @@ pub struct Blueprint {
 }
 
 impl Blueprint {
-  fn parse(line: &str) -> Self {
-    let words: Vec<&str> = line.split_whitespace().collect();
-    let id = words[1][..words[1].len()-1].parse().unwrap();
+  /// Parse "Blueprint 1: Each ore robot costs 4 ore. Each obsidian robot costs
+  /// 3 ore and 14 clay. ..."
+  fn parse(cursor: &mut Cursor) -> parse::Result<Self> {
+    cursor.literal("Blueprint")?;
+    let id = cursor.integer()?;
+    cursor.literal(":")?;
     let mut robot = [[0; Resource::SIZE]; Resource::SIZE];
-    robot[Resource::Ore.idx()][Resource::Ore.idx()] = words[6].parse().unwrap();
-    robot[Resource::Clay.idx()][Resource::Ore.idx()] = words[12].parse().unwrap();
-    robot[Resource::Obsidian.idx()][Resource::Ore.idx()] = words[18].parse().unwrap();
-    robot[Resource::Obsidian.idx()][Resource::Clay.idx()] = words[21].parse().unwrap();
-    robot[Resource::Geode.idx()][Resource::Ore.idx()] = words[27].parse().unwrap();
-    robot[Resource::Geode.idx()][Resource::Obsidian.idx()] = words[30].parse().unwrap();
+    while cursor.accept("Each") {
+      let kind = Resource::parse(cursor)?;
+      cursor.literal("robot costs")?;
+      let costs = cursor.separated("and", |c| Ok((c.integer()?, Resource::parse(c)?)))?;
+      for (count, resource) in costs {
+        robot[kind.idx()][resource.idx()] = count;
+      }
+      cursor.literal(".")?;
+    }
     let mut max_robots = [0; Resource::SIZE];
     for r in Resource::iter() {
       max_robots[r.idx()] = robot.iter().map(|col| col[r.idx()]).max().unwrap();
     }
     max_robots[Resource::Geode.idx()] = Count::MAX;
-    Blueprint{id, robot, max_robots}
+    Ok(Blueprint{id, robot, max_robots})
   }
 }
 
 pub fn generator(input: &str) -> InputType {
-  input.lines().map(Blueprint::parse).collect()
+  parse::parse(input, |c| c.repeat(Blueprint::parse))
+    .unwrap_or_else(|e| panic!("Can't parse blueprints: {e}"))
 }
 
 #[derive(Clone,Debug,Eq,Hash,PartialEq)]
@@ -88,12 +105,11 @@ impl State {
     let mut idle_time = 0;
     for r in (0..Resource::SIZE).rev() {
       if self.stock[r] < blueprint.robot[robot_idx][r] {
-        if self.robots[r] > 0 {
-          let needed = blueprint.robot[robot_idx][r] - self.stock[r];
-          idle_time = Count::max(idle_time, (needed + self.robots[r] - 1) / self.robots[r]);
-        } else {
+        if self.robots[r] == 0 {
           return None
         }
+        let needed = blueprint.robot[robot_idx][r] - self.stock[r];
+        idle_time = Count::max(idle_time, needed.div_ceil(self.robots[r]));
       }
     }
     if idle_time >= self.remaining_time {
@@ -181,29 +197,47 @@ impl State {
   }
 }
 
-fn best_score(blueprint: &Blueprint, time: Count) -> Count {
-  let mut pending = Vec::new();
-  pending.push(State::new(time));
-  let mut max = 0;
-  while let Some(state) = pending.pop() {
-    max = Count::max(max, state.stock[Resource::Geode.idx()]);
-    if state.remaining_time > 0 {
-      pending.extend(state.next(blueprint).into_iter()
-        .filter(|s| s.limit(blueprint) > max));
+impl Bounded for State {
+  type Context = Blueprint;
+  type Score = Count;
+
+  fn branches(&self, blueprint: &Blueprint) -> Vec<Self> {
+    if self.remaining_time > 0 {
+      self.next(blueprint)
+    } else {
+      Vec::new()
     }
   }
-  max
+
+  fn score(&self) -> Count {
+    self.stock[Resource::Geode.idx()]
+  }
+
+  fn upper_bound(&self, blueprint: &Blueprint) -> Count {
+    self.limit(blueprint)
+  }
+}
+
+fn best_score(blueprint: &Blueprint, time: Count) -> Count {
+  search::maximize(blueprint, State::new(time)).score()
 }
 
 pub fn part1(input: &InputType) -> OutputType {
-  input.iter()
-      .map(|bp| bp.id * best_score(bp, TIME) as usize)
+  input.iter().enumerate()
+      .map(|(i, bp)| {
+        progress::report(|| format!("blueprint {}/{}", i + 1, input.len()));
+        bp.id * best_score(bp, TIME) as usize
+      })
       .sum()
 }
 
 pub fn part2(input: &InputType) -> OutputType {
-  input.iter().take(PART2_PLAN_LIMIT)
-      .map(|bp| best_score(bp, PART2_TIME) as usize)
+  let count = input.len().min(PART2_PLAN_LIMIT);
+  input.iter().take(count).enumerate()
+      .map(|(i, bp)| {
+        progress::report(|| format!("blueprint {}/{count}", i + 1));
+        best_score(bp, PART2_TIME) as usize
+      })
       .product()
 }
 
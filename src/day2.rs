@@ -1,80 +1,346 @@
-#[derive(Clone,Copy,Debug,Eq,PartialEq)]
-pub enum Move {
-  ROCK,
-  PAPER,
-  SCISSORS,
-}
+use std::cmp::Reverse;
+use std::fmt::Write;
+use itertools::Itertools;
+use serde::Serialize;
+use crate::utils::Answer;
+
+/// A move, which is its position in the game's cycle of moves.
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub struct Move(usize);
 
 impl Move {
-  /// Convert a string to a move
-  fn from_str(s: &str) -> Move {
-    match s {
-      "A" | "X" => Self::ROCK,
-      "B" | "Y" => Self::PAPER,
-      "C" | "Z" => Self::SCISSORS,
-      _ => panic!("can't parse {s}"),
+  /// The moves of rock, paper, scissors.
+  pub const ROCK: Move = Move(0);
+  pub const PAPER: Move = Move(1);
+  pub const SCISSORS: Move = Move(2);
+
+  /// The ordinal of the move
+  pub fn value(&self) -> usize {
+    self.0
+  }
+
+  /// Compute the score of a turn.
+  pub fn turn_eval(&self, other: &Self, game: &Game) -> i32 {
+    game.scoring.moves[self.0] + game.scoring.outcomes[game.outcome(*self, *other) as usize]
+  }
+
+  /// Find our move that gives the required result against the other
+  /// player's move. If several do, pick the one that scores the most.
+  pub fn find_my_move(result: Outcome, other: &Self, game: &Game) -> Self {
+    game.moves().filter(|&mine| game.outcome(mine, *other) == result)
+      .max_by_key(|mine| (game.scoring.moves[mine.0], Reverse(mine.0)))
+      .expect("every game has a move for each result")
+  }
+}
+
+/// The result of a turn for the player making the move.
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub enum Outcome {
+  Lose,
+  Draw,
+  Win,
+}
+
+impl Outcome {
+  pub const ALL: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+}
+
+/// The points for each move and each result of a turn.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct Scoring {
+  /// The points for making each move.
+  pub moves: Vec<i32>,
+  /// The points for losing, drawing, and winning.
+  pub outcomes: [i32; 3],
+}
+
+/// A game where the moves form a cycle and each move beats the half of the
+/// other moves that come right before it, like rock, paper, scissors.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct Game {
+  names: Vec<String>,
+  /// The letter for each move in the first column of the guide.
+  their_codes: Vec<char>,
+  /// The letter in the second column of the guide for each move.
+  my_codes: Vec<char>,
+  /// The letters in the second column that mean lose, draw, and win in
+  /// part 2.
+  result_codes: Vec<char>,
+  scoring: Scoring,
+}
+
+impl Game {
+  /// Define a game with the moves in cycle order, the letters that stand for
+  /// them in each column, the letters for the results, and the scores. There
+  /// must be an odd number of moves, so that every pair of different moves
+  /// has a winner.
+  pub fn new(names: &[&str], their_codes: &str, my_codes: &str, result_codes: &str,
+             scoring: Scoring) -> Result<Self, String> {
+    let size = names.len();
+    if size < 3 || size.is_multiple_of(2) {
+      return Err(format!("need an odd number of moves, at least 3, not {size}"))
+    }
+    let their_codes: Vec<char> = their_codes.chars().collect();
+    let my_codes: Vec<char> = my_codes.chars().collect();
+    let result_codes: Vec<char> = result_codes.chars().collect();
+    for (codes, count) in [(&their_codes, size), (&my_codes, size),
+                           (&result_codes, Outcome::ALL.len())] {
+      if codes.len() != count {
+        return Err(format!("need {count} letters, not {codes:?}"))
+      }
+      if (1..count).any(|i| codes[..i].contains(&codes[i])) {
+        return Err(format!("letters repeat in {codes:?}"))
+      }
+    }
+    if let Some(ch) = result_codes.iter().find(|ch| !my_codes.contains(ch)) {
+      return Err(format!("result letter '{ch}' isn't one of {my_codes:?}"))
+    }
+    if scoring.moves.len() != size {
+      return Err(format!("need {size} move scores, not {:?}", scoring.moves))
     }
+    Ok(Game{names: names.iter().map(|n| n.to_string()).collect(), their_codes, my_codes,
+            result_codes, scoring})
   }
 
-  /// Parse a line into the two moves
-  fn parse(s: &str) -> Vec<Self> {
-    s.split_whitespace().map(Self::from_str).collect()
+  /// The puzzle's game.
+  pub fn rock_paper_scissors() -> Self {
+    Self::new(&["rock", "paper", "scissors"], "ABC", "XYZ", "XYZ",
+              Scoring{moves: vec![1, 2, 3], outcomes: [0, 3, 6]}).unwrap()
   }
 
-  /// The ordinal of the move
-  fn value(&self) -> i32 {
-    *self as i32
+  /// The five move version, where each move beats two others.
+  pub fn rock_paper_scissors_lizard_spock() -> Self {
+    Self::new(&["rock", "spock", "paper", "lizard", "scissors"], "ABCDE", "VWXYZ",
+              "XYZ", Scoring{moves: vec![1, 2, 3, 4, 5], outcomes: [0, 3, 6]}).unwrap()
+  }
+
+  /// The number of moves.
+  pub fn size(&self) -> usize {
+    self.names.len()
+  }
+
+  pub fn moves(&self) -> impl Iterator<Item=Move> {
+    (0..self.size()).map(Move)
+  }
+
+  pub fn name(&self, m: Move) -> &str {
+    &self.names[m.0]
   }
 
-  /// Convert from the ordinal
-  fn from_i32(v: i32) -> Self {
-    match v {
-      0 => Self::ROCK,
-      1 => Self::PAPER,
-      2 => Self::SCISSORS,
-      _ => panic!("bad value {v}"),
+  /// Does my move beat, tie, or lose to theirs? A move beats the ones up to
+  /// half way back around the cycle.
+  pub fn outcome(&self, mine: Move, theirs: Move) -> Outcome {
+    match (mine.0 + self.size() - theirs.0) % self.size() {
+      0 => Outcome::Draw,
+      d if d <= self.size() / 2 => Outcome::Win,
+      _ => Outcome::Lose,
     }
   }
 
-  /// Compute the score of a turn.
-  fn turn_eval(&self, other: &Self) -> i32 {
-    let result_points = match (self.value() - other.value() + 3) % 3 {
-      0 => 3,
-      1 => 6,
-      2 => 0,
-      _ => panic!("bad difference {:?} - {:?}", self, other)
+  /// Parse a line of the guide. Part 1 reads the second column as my move
+  /// and part 2 as a result, so its letter must be a move and, if part 2 is
+  /// going to be run, a result.
+  fn parse_round(&self, line: &str) -> Result<Round, String> {
+    let mut words = line.split_whitespace();
+    let mut letter = || {
+      let word = words.next().ok_or_else(|| format!("missing a column in '{line}'"))?;
+      let mut letters = word.chars();
+      match (letters.next(), letters.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(format!("expected a letter, not '{word}' in '{line}'")),
+      }
     };
-    self.value() + 1 + result_points
+    let position = |codes: &[char], ch: char| codes.iter().position(|&c| c == ch)
+      .ok_or_else(|| format!("unknown letter '{ch}' in '{line}'"));
+    let theirs = Move(position(&self.their_codes, letter()?)?);
+    let mine = letter()?;
+    let column = position(&self.my_codes, mine)?;
+    let result = self.result_codes.iter().position(|&c| c == mine)
+      .map(|i| Outcome::ALL[i]);
+    Ok(Round{theirs, column, result})
   }
 
-  /// Find our required move from the result of the turn
-  /// and the other player's move.
-  /// self: X/Rock => lose, Y/Paper => tie, Z/Scissors => win
-  fn find_my_move(&self, other: &Self) -> Self {
-    // subtract one from the result so that Y == 0
-    Self::from_i32((self.value() - 1 + other.value() + 3) % 3)
+  /// Parse a strategy guide for this game.
+  pub fn parse_guide(self, input: &str) -> Result<Guide, String> {
+    let rounds = input.lines().map(|line| self.parse_round(line))
+      .collect::<Result<_, _>>()?;
+    Ok(Guide{game: self, rounds})
   }
 }
 
-pub fn generator(input: &str) -> Vec<Vec<Move>> {
-  input.lines().map(Move::parse).collect()
+/// A line of the strategy guide.
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
+pub struct Round {
+  pub theirs: Move,
+  /// Which of the second column's letters the line has.
+  pub column: usize,
+  /// The result that part 2 reads from the second column, if its letter is
+  /// one of the result letters.
+  pub result: Option<Outcome>,
 }
 
-pub fn part1(input: &[Vec<Move>]) -> i32 {
-  input.iter()
-    .map(|v| v[1].turn_eval(&v[0]))
-    .sum()
+impl Round {
+  /// Part 1 reads the second column as my move.
+  fn my_move(&self) -> Move {
+    Move(self.column)
+  }
+}
+
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct Guide {
+  game: Game,
+  rounds: Vec<Round>,
+}
+
+/// The best and worst totals over the ways of reading the second column,
+/// with the reading of each letter that gives them.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct Extremes<T> {
+  pub best: (Vec<T>, i32),
+  pub worst: (Vec<T>, i32),
+}
+
+/// What a strategy guide says about the opponent and how well it could be
+/// answered.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct Analysis {
+  /// How many times the opponent plays each move.
+  pub distribution: Vec<usize>,
+  /// The total for always playing each move. Picking the moves at random
+  /// scores a weighted average of these, so against this guide no mixed
+  /// strategy beats the best fixed move.
+  pub fixed_totals: Vec<i32>,
+  pub best_fixed: (Move, i32),
+  /// The best reply to each of the opponent's moves and the total from
+  /// always using it.
+  pub best_replies: (Vec<Move>, i32),
+  /// Reading each letter of the second column as a different move.
+  pub as_moves: Extremes<Move>,
+  /// Reading each result letter as a different result, if the guide only
+  /// uses result letters.
+  pub as_results: Option<Extremes<Outcome>>,
+}
+
+impl Guide {
+  /// Count the lines with each of the opponent's moves and second column
+  /// letters, where `letter` gives the letter's index among `width` of them.
+  fn counts(&self, width: usize, letter: impl Fn(&Round) -> usize) -> Vec<Vec<i32>> {
+    let mut result = vec![vec![0; width]; self.game.size()];
+    for round in &self.rounds {
+      result[round.theirs.0][letter(round)] += 1;
+    }
+    result
+  }
+
+  /// Try every way of giving each of the letters a different one of the
+  /// options and find the best and worst totals. The letter function gives a
+  /// line's letter as an index into the options, and the score function
+  /// rates a turn given the letter's option and the opponent's move.
+  fn extremes<T: Copy>(&self, options: &[T], letter: impl Fn(&Round) -> usize,
+                       score: impl Fn(T, Move) -> i32) -> Extremes<T> {
+    let counts = self.counts(options.len(), letter);
+    let mut best: Option<(Vec<T>, i32)> = None;
+    let mut worst: Option<(Vec<T>, i32)> = None;
+    for reading in options.iter().copied().permutations(options.len()) {
+      let total = counts.iter().enumerate()
+        .flat_map(|(theirs, row)| row.iter().enumerate()
+          .filter(|(_, &n)| n > 0)
+          .map(move |(column, &n)| (Move(theirs), column, n)))
+        .map(|(theirs, column, n)| n * score(reading[column], theirs))
+        .sum();
+      if best.as_ref().is_none_or(|(_, b)| total > *b) {
+        best = Some((reading.clone(), total));
+      }
+      if worst.as_ref().is_none_or(|(_, w)| total < *w) {
+        worst = Some((reading, total));
+      }
+    }
+    Extremes{best: best.unwrap(), worst: worst.unwrap()}
+  }
+
+  /// Work out the opponent's habits, the best ways to answer them, and
+  /// what the guide could score if its second column meant something else.
+  pub fn analyze(&self) -> Analysis {
+    let game = &self.game;
+    let distribution: Vec<usize> = self.counts(game.size(), |r| r.column).iter()
+      .map(|row| row.iter().sum::<i32>() as usize).collect();
+    let total = |theirs: Move, mine: Move| distribution[theirs.0] as i32
+      * mine.turn_eval(&theirs, game);
+    let fixed_totals: Vec<i32> = game.moves()
+      .map(|mine| game.moves().map(|theirs| total(theirs, mine)).sum())
+      .collect();
+    let best_fixed = game.moves().map(|mine| (mine, fixed_totals[mine.0]))
+      .max_by_key(|&(mine, t)| (t, Reverse(mine.0))).unwrap();
+    let replies: Vec<Move> = game.moves()
+      .map(|theirs| game.moves()
+        .max_by_key(|mine| (mine.turn_eval(&theirs, game), Reverse(mine.0))).unwrap())
+      .collect();
+    let replies_total = game.moves().map(|theirs| total(theirs, replies[theirs.0])).sum();
+    let as_moves = self.extremes(&game.moves().collect::<Vec<_>>(), |r| r.column,
+                                 |mine, theirs| mine.turn_eval(&theirs, game));
+    let as_results = self.rounds.iter().all(|r| r.result.is_some())
+      .then(|| self.extremes(&Outcome::ALL, |r| r.result.unwrap() as usize,
+        |result, theirs| Move::find_my_move(result, &theirs, game).turn_eval(&theirs, game)));
+    Analysis{distribution, fixed_totals, best_fixed, best_replies: (replies, replies_total),
+             as_moves, as_results}
+  }
+
+  /// Describe the analysis for a person.
+  pub fn report(&self) -> String {
+    let game = &self.game;
+    let analysis = self.analyze();
+    let names = |moves: &[Move]| moves.iter().map(|&m| game.name(m)).join(", ");
+    let mut out = String::new();
+    writeln!(out, "opponent: {}", game.moves()
+      .map(|m| format!("{} {}", game.name(m), analysis.distribution[m.0])).join(", ")).unwrap();
+    writeln!(out, "best fixed move: {} for {}", game.name(analysis.best_fixed.0),
+             analysis.best_fixed.1).unwrap();
+    writeln!(out, "best replies: {} for {}", names(&analysis.best_replies.0),
+             analysis.best_replies.1).unwrap();
+    let letters = game.my_codes.iter().join("");
+    writeln!(out, "{letters} as moves: best {} for {}, worst {} for {}",
+             names(&analysis.as_moves.best.0), analysis.as_moves.best.1,
+             names(&analysis.as_moves.worst.0), analysis.as_moves.worst.1).unwrap();
+    if let Some(results) = &analysis.as_results {
+      writeln!(out, "{} as results: best {:?} for {}, worst {:?} for {}",
+               game.result_codes.iter().collect::<String>(),
+               results.best.0, results.best.1, results.worst.0, results.worst.1).unwrap();
+    }
+    out
+  }
+}
+
+pub fn generator(input: &str) -> Guide {
+  Game::rock_paper_scissors().parse_guide(input)
+    .unwrap_or_else(|e| panic!("Can't parse guide: {e}"))
 }
 
-pub fn part2(input: &[Vec<Move>]) -> i32 {
-  input.iter()
-    .map(|v| v[1].find_my_move(&v[0]).turn_eval(&v[0]))
+pub fn part1(input: &Guide) -> i32 {
+  input.rounds.iter()
+    .map(|r| r.my_move().turn_eval(&r.theirs, &input.game))
     .sum()
 }
 
+/// Part 2 reads the second column as the result that I need, so it fails if
+/// a line's letter isn't one of the result letters.
+pub fn part2(input: &Guide) -> Answer {
+  let game = &input.game;
+  let mut total = 0;
+  for (line, round) in input.rounds.iter().enumerate() {
+    let Some(result) = round.result else {
+      return Answer::Error(format!("line {} has '{}', which isn't one of the results {}",
+                                   line + 1, game.my_codes[round.column],
+                                   game.result_codes.iter().join("")))
+    };
+    total += Move::find_my_move(result, &round.theirs, game).turn_eval(&round.theirs, game);
+  }
+  total.into()
+}
+
 #[cfg(test)]
 mod tests {
-  use crate::day2::{generator, Move, part1, part2};
+  use crate::day2::{generator, Extremes, Game, Move, Outcome, part1, part2, Round, Scoring};
+  use crate::utils::Answer;
 
   const INPUT: &str = "A Y\n\
                        B X\n\
@@ -82,10 +348,86 @@ mod tests {
 
   #[test]
   fn parsing_test() {
-    let moves = generator(INPUT);
-    assert_eq!(vec! {vec! {Move::ROCK, Move::PAPER},
-                     vec! {Move::PAPER, Move::ROCK},
-                     vec! {Move::SCISSORS, Move::SCISSORS}}, moves);
+    let guide = generator(INPUT);
+    assert_eq!(vec![Round{theirs: Move::ROCK, column: 1, result: Some(Outcome::Draw)},
+                    Round{theirs: Move::PAPER, column: 0, result: Some(Outcome::Lose)},
+                    Round{theirs: Move::SCISSORS, column: 2, result: Some(Outcome::Win)}],
+               guide.rounds);
+    let game = Game::rock_paper_scissors();
+    assert_eq!(Err("unknown letter 'D' in 'A D'".to_string()),
+               game.clone().parse_guide("A D").map(|g| g.rounds));
+    assert_eq!(Err("missing a column in 'A'".to_string()),
+               game.parse_guide("A").map(|g| g.rounds));
+  }
+
+  #[test]
+  fn test_game() {
+    let rps = Game::rock_paper_scissors();
+    assert_eq!(Outcome::Win, rps.outcome(Move::PAPER, Move::ROCK));
+    assert_eq!(Outcome::Lose, rps.outcome(Move::PAPER, Move::SCISSORS));
+    assert_eq!(Outcome::Draw, rps.outcome(Move::ROCK, Move::ROCK));
+    assert_eq!(Outcome::Win, rps.outcome(Move::ROCK, Move::SCISSORS));
+    let rpsls = Game::rock_paper_scissors_lizard_spock();
+    let names = |result| rpsls.moves()
+      .filter(|&m| rpsls.outcome(m, Move(3)) == result)
+      .map(|m| rpsls.name(m)).collect::<Vec<_>>();
+    assert_eq!(vec!["rock", "scissors"], names(Outcome::Win));
+    assert_eq!(vec!["spock", "paper"], names(Outcome::Lose));
+    // the winner that scores the most
+    assert_eq!("scissors", rpsls.name(Move::find_my_move(Outcome::Win, &Move(3), &rpsls)));
+    assert_eq!(5 + 6, Move(4).turn_eval(&Move(3), &rpsls));
+    assert!(Game::new(&["a", "b", "c", "d"], "ABCD", "WXYZ", "XYZ",
+                      Scoring{moves: vec![1, 2, 3, 4], outcomes: [0, 3, 6]}).is_err());
+    assert!(Game::new(&["a", "b", "c"], "ABA", "XYZ", "XYZ",
+                      Scoring{moves: vec![1, 2, 3], outcomes: [0, 3, 6]}).is_err());
+    assert!(Game::new(&["a", "b", "c"], "ABC", "XYZ", "XY",
+                      Scoring{moves: vec![1, 2, 3], outcomes: [0, 3, 6]}).is_err());
+    assert!(Game::new(&["a", "b", "c"], "ABC", "XYZ", "XYQ",
+                      Scoring{moves: vec![1, 2, 3], outcomes: [0, 3, 6]}).is_err());
+  }
+
+  #[test]
+  fn test_scoring() {
+    let game = Game::new(&["rock", "paper", "scissors"], "RPS", "rps", "rps",
+                         Scoring{moves: vec![0, 0, 0], outcomes: [-1, 0, 1]}).unwrap();
+    let guide = game.parse_guide("R p\nS p\nP p").unwrap();
+    assert_eq!(0, part1(&guide));
+    assert_eq!(Answer::Integer(0), part2(&guide));
+  }
+
+  #[test]
+  fn test_analyze() {
+    let analysis = generator(INPUT).analyze();
+    assert_eq!(vec![1, 1, 1], analysis.distribution);
+    assert_eq!(vec![12, 15, 18], analysis.fixed_totals);
+    assert_eq!((Move::SCISSORS, 18), analysis.best_fixed);
+    assert_eq!((vec![Move::PAPER, Move::SCISSORS, Move::ROCK], 24), analysis.best_replies);
+    assert_eq!(Extremes{best: (vec![Move::SCISSORS, Move::PAPER, Move::ROCK], 24),
+                        worst: (vec![Move::ROCK, Move::SCISSORS, Move::PAPER], 6)},
+               analysis.as_moves);
+    assert_eq!(Some(Extremes{best: (vec![Outcome::Win, Outcome::Lose, Outcome::Draw], 18),
+                             worst: (vec![Outcome::Lose, Outcome::Draw, Outcome::Win], 12)}),
+               analysis.as_results);
+    assert_eq!("opponent: rock 1, paper 1, scissors 1\n\
+                best fixed move: scissors for 18\n\
+                best replies: paper, scissors, rock for 24\n\
+                XYZ as moves: best scissors, paper, rock for 24, worst rock, scissors, paper for 6\n\
+                XYZ as results: best [Win, Lose, Draw] for 18, worst [Lose, Draw, Win] for 12\n",
+               generator(INPUT).report());
+    let rpsls = Game::rock_paper_scissors_lizard_spock().parse_guide("A Z\nE Y").unwrap();
+    // the results are read from XYZ, which are the last three move letters
+    assert_eq!(Some(Extremes{best: (vec![Outcome::Lose, Outcome::Draw, Outcome::Win], 17),
+                             worst: (vec![Outcome::Win, Outcome::Lose, Outcome::Draw], 8)}),
+               rpsls.analyze().as_results);
+    assert!(rpsls.report().ends_with("XYZ as results: best [Lose, Draw, Win] for 17, \
+                                      worst [Win, Lose, Draw] for 8\n"));
+    let moves_only = Game::rock_paper_scissors_lizard_spock().parse_guide("A Z\nE V").unwrap();
+    assert_eq!(None, moves_only.analyze().as_results);
+    let greek = Game::new(&["rock", "paper", "scissors"], "αβγ", "χψω", "χψω",
+                          Scoring{moves: vec![1, 2, 3], outcomes: [0, 3, 6]}).unwrap();
+    assert!(greek.parse_guide("α ψ\nβ χ\nγ ω").unwrap().report()
+      .ends_with("χψω as results: best [Win, Lose, Draw] for 18, \
+                  worst [Lose, Draw, Win] for 12\n"));
   }
 
   #[test]
@@ -95,6 +437,22 @@ mod tests {
 
   #[test]
   fn test_part2() {
-    assert_eq!(12, part2(&generator(INPUT)))
+    assert_eq!(Answer::Integer(12), part2(&generator(INPUT)))
+  }
+
+  #[test]
+  fn test_rpsls_part2() {
+    let guide = Game::rock_paper_scissors_lizard_spock().parse_guide("A Y\nB Z").unwrap();
+    // draw with rock, then beat spock with lizard rather than paper
+    assert_eq!(Answer::Integer(1 + 3 + 4 + 6), part2(&guide));
+  }
+
+  #[test]
+  fn test_rpsls_part1() {
+    // V and W are rock and spock, which aren't results
+    let guide = Game::rock_paper_scissors_lizard_spock().parse_guide("A W\nB V\nE Z").unwrap();
+    assert_eq!(2 + 6 + 1 + 5 + 3, part1(&guide));
+    assert_eq!(Answer::Error("line 1 has 'W', which isn't one of the results XYZ".to_string()),
+               part2(&guide));
   }
 }
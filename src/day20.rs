@@ -1,5 +1,7 @@
 use std::cmp::Ordering;
 
+use crate::utils::checked;
+
 type InputType = Vec<Num>;
 type OutputType = Num;
 
@@ -126,7 +128,7 @@ const ITERATIONS: usize = 10;
 pub fn part2(input: &InputType) -> OutputType {
   let mut list = DoubleLinkedList::default();
   for n in input {
-    list.push(*n * DECRYPTION_KEY);
+    list.push(checked::mul(*n, DECRYPTION_KEY, "applying DECRYPTION_KEY"));
   }
   for _ in 0..ITERATIONS {
     list.shuffle();
@@ -1,13 +1,16 @@
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::rc::Rc;
+use serde::Serialize;
+use crate::utils::anonymize::{self, Anonymized, AnswerMap, Rng};
+use crate::utils::checked;
 
 type InputType = Vec<Monkey>;
 type OutputType = Num;
 
 type Num = i64;
 
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,Serialize)]
 enum Operation {
   Literal(Num),
   Plus,
@@ -32,10 +35,10 @@ impl Operation {
   fn evaluate(&self, left: Num, right: Num) -> Num {
     match self {
       Operation::Literal(n) => *n,
-      Operation::Plus => left + right,
-      Operation::Minus => left - right,
-      Operation::Multiply => left * right,
-      Operation::Divide => left / right,
+      Operation::Plus => checked::add(left, right, "Operation::Plus"),
+      Operation::Minus => checked::sub(left, right, "Operation::Minus"),
+      Operation::Multiply => checked::mul(left, right, "Operation::Multiply"),
+      Operation::Divide => checked::div(left, right, "Operation::Divide"),
       Operation::Equals => if left == right { 1 } else { 0 },
       _ => panic!("Can't handle operation {self:?}"),
     }
@@ -43,26 +46,26 @@ impl Operation {
 
   fn evaluate_for_left(&self, right: Num, result: Num) -> Num {
     match self {
-      Operation::Plus => result - right,
-      Operation::Minus => result + right,
-      Operation::Multiply => result / right,
-      Operation::Divide => result * right,
+      Operation::Plus => checked::sub(result, right, "Operation::Plus"),
+      Operation::Minus => checked::add(result, right, "Operation::Minus"),
+      Operation::Multiply => checked::div(result, right, "Operation::Multiply"),
+      Operation::Divide => checked::mul(result, right, "Operation::Divide"),
       _ => panic!("Can't handle operation {self:?}"),
     }
   }
 
   fn evaluate_for_right(&self, left: Num, result: Num) -> Num {
     match self {
-      Operation::Plus => result - left,
-      Operation::Minus => left - result,
-      Operation::Multiply => result / left,
-      Operation::Divide => left / result,
+      Operation::Plus => checked::sub(result, left, "Operation::Plus"),
+      Operation::Minus => checked::sub(left, result, "Operation::Minus"),
+      Operation::Multiply => checked::div(result, left, "Operation::Multiply"),
+      Operation::Divide => checked::div(left, result, "Operation::Divide"),
       _ => panic!("Can't handle operation {self:?}"),
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Monkey {
   name: String,
   op: Operation,
@@ -114,18 +117,10 @@ impl Calculation {
     }
     let result = match results[monkey].op {
       Operation::Literal(num) => { num },
-      Operation::Plus => {
-        Self::evaluate(results, results[monkey].parameters[0]) +
-            Self::evaluate(results, results[monkey].parameters[1])},
-      Operation::Minus => {
-        Self::evaluate(results, results[monkey].parameters[0]) -
-            Self::evaluate(results, results[monkey].parameters[1])},
-      Operation::Multiply => {
-        Self::evaluate(results, results[monkey].parameters[0]) *
-            Self::evaluate(results, results[monkey].parameters[1])},
-      Operation::Divide => {
-        Self::evaluate(results, results[monkey].parameters[0]) /
-            Self::evaluate(results, results[monkey].parameters[1])},
+      op @ (Operation::Plus | Operation::Minus | Operation::Multiply | Operation::Divide) => {
+        let left = Self::evaluate(results, results[monkey].parameters[0]);
+        let right = Self::evaluate(results, results[monkey].parameters[1]);
+        op.evaluate(left, right)},
       _ => panic!("Can't handle {:?}", results[monkey].op),
     };
     results[monkey].cache = Some(result);
@@ -245,9 +240,18 @@ pub fn part2(input: &InputType) -> OutputType {
   panic!("Not simplified {ans:?}");
 }
 
+/// Rename every monkey except root and the human, and shuffle the lines.
+pub fn anonymize(input: &str, rng: &mut Rng) -> Anonymized {
+  let names = input.lines().filter_map(|line| line.split_once(':')).map(|(name, _)| name);
+  let renames = anonymize::rename(names, &["root", "humn"], 'a'..='z', rng);
+  let text = anonymize::shuffle_lines(&anonymize::replace_words(input, &renames), rng);
+  Anonymized{text, answers: AnswerMap::Same}
+}
+
 #[cfg(test)]
 mod tests {
-  use crate::day21::{generator, part1, part2};
+  use crate::day21::{anonymize, generator, part1, part2};
+  use crate::utils::anonymize::Rng;
 
   #[test]
   fn test_part1() {
@@ -260,6 +264,15 @@ mod tests {
     assert_eq!(301, part2(&generator(INPUT)));
   }
 
+  #[test]
+  fn test_anonymize() {
+    let anonymized = anonymize(INPUT, &mut Rng::new(4));
+    assert!(!anonymized.text.contains("pppw"));
+    let input = generator(&anonymized.text);
+    assert_eq!(152, part1(&input));
+    assert_eq!(301, part2(&input));
+  }
+
   const INPUT: &str = "root: pppw + sjmn\n\
                        dbpl: 5\n\
                        cczh: sllz + lgvd\n\
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use itertools::Itertools;
+use serde::Serialize;
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::grid::Grid;
 
-#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+type Point = Point2<i32>;
+
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
 enum Spot {
   Wall,
   Floor,
@@ -20,54 +25,46 @@ impl Spot {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 enum Move {
   Left,
   Right,
   Forward(i32),
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 struct Map {
-  walls: Vec<Vec<Spot>>,
-  size: (i32, i32),
+  walls: Grid<Spot>,
+  size: Point,
   face_size: i32,
 }
 
 impl Map {
   fn parse(input: &str) -> Self {
-    let walls:Vec<Vec<Spot>> = input.lines()
-      .map(|l| l.chars().map(Spot::parse).collect())
-      .collect();
-    let width = walls.iter().map(|r| r.len()).max().unwrap_or(0) as i32;
-    let height = walls.len() as i32;
+    let walls = Grid::parse(input, Spot::parse);
+    let width = walls.width() as i32;
+    let height = walls.height() as i32;
     // Flattened cubes are either 3x4 or 2x5
     let long_side = width.max(height);
     let short_side = width.min(height);
     if long_side / short_side >= 2 {
-      Map{walls, size: (width, height), face_size: short_side / 2}
+      Map{walls, size: Point::new(width, height), face_size: short_side / 2}
     } else {
-      Map{walls, size: (width, height), face_size: short_side / 3}
+      Map{walls, size: Point::new(width, height), face_size: short_side / 3}
     }
   }
 
   /// Wrap coordinates within the map's ranges
-  fn wrap_locations(&self, xy: (i32, i32)) -> (i32,i32) {
-    (xy.0.rem_euclid(self.size.0), xy.1.rem_euclid(self.size.1))
+  fn wrap_locations(&self, xy: Point) -> Point {
+    Point::new(xy.x.rem_euclid(self.size.x), xy.y.rem_euclid(self.size.y))
   }
 
-  fn get(&self, x: i32, y: i32) -> Spot {
-    if (0..self.size.0).contains(&x) && (0..self.size.1).contains(&y) {
-      let row = &self.walls[y as usize];
-      if x < row.len() as i32 {
-        return row[x as usize];
-      }
-    }
-    Spot::Void
+  fn get(&self, xy: Point) -> Spot {
+    self.walls.get(xy.x, xy.y).copied().unwrap_or(Spot::Void)
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct InputType {
   map: Map,
   moves: Vec<Move>,
@@ -98,72 +95,20 @@ pub fn generator(input: &str) -> InputType {
     moves: InputType::parse_moves(move_text)}
 }
 
-#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
-enum Direction {
-  Right,
-  Down,
-  Left,
-  Up,
-}
-
-impl Direction {
-  fn left(&self) -> Self {
-    match self {
-      Direction::Up => Direction::Left,
-      Direction::Left => Direction::Down,
-      Direction::Down => Direction::Right,
-      Direction::Right => Direction::Up,
-    }
-  }
-
-  fn right(&self) -> Self {
-    match self {
-      Direction::Up => Direction::Right,
-      Direction::Left => Direction::Up,
-      Direction::Down => Direction::Left,
-      Direction::Right => Direction::Down,
-    }
-  }
-
-  fn delta(&self) -> (i32, i32) {
-    match self {
-      Direction::Up => (0, -1),
-      Direction::Left => (-1, 0),
-      Direction::Down => (0, 1),
-      Direction::Right => (1, 0),
-    }
-  }
-
-  fn advance(&self, xy: (i32, i32)) -> (i32, i32) {
-    let delta = self.delta();
-    (xy.0 + delta.0, xy.1 + delta.1)
-  }
-
-  fn flip(&self) -> Self {
-    match self {
-      Direction::Up => Direction::Down,
-      Direction::Down => Direction::Up,
-      Direction::Left => Direction::Right,
-      Direction::Right => Direction::Left,
-    }
-  }
-}
-
 /// A map coordinate & facing direction for describing the source and destination of the jumps
 /// through the void.
 #[derive(Clone,Debug,Eq,Hash,PartialEq)]
 struct VoidConnection {
-  xy: (i32, i32),
-  facing: Direction,
+  xy: Point,
+  facing: Dir4,
 }
 
 #[derive(Debug)]
 struct State<Func>
   where Func: Fn(VoidConnection) -> VoidConnection,
 {
-  x: i32,
-  y: i32,
-  facing: Direction,
+  xy: Point,
+  facing: Dir4,
   void_jump: Func,
 }
 
@@ -171,19 +116,18 @@ impl<Func> State<Func>
   where Func: Fn(VoidConnection) -> VoidConnection,
 {
   fn new(input: &InputType, void_jump: Func) -> Self {
-    let x = input.map.walls[0].iter().enumerate()
-      .find(|(_, &s)| s == Spot::Floor).unwrap().0 as i32;
-    State{x, y: 0, facing: Direction::Right, void_jump}
+    let x = input.map.walls.row(0).iter()
+      .position(|&s| s == Spot::Floor).unwrap() as i32;
+    State{xy: Point::new(x, 0), facing: Dir4::East, void_jump}
   }
 
   fn forward(&mut self, distance: i32, map: &Map) {
     for _ in 0..distance {
-      let mut next = VoidConnection{xy: (self.x, self.y), facing: self.facing};
-      next.xy = next.facing.advance(next.xy);
+      let mut next = VoidConnection{xy: self.xy + self.facing.delta(), facing: self.facing};
       loop {
-        match map.get(next.xy.0, next.xy.1) {
+        match map.get(next.xy) {
           Spot::Floor => {
-            (self.x, self.y) = next.xy;
+            self.xy = next.xy;
             self.facing = next.facing;
             break;
           },
@@ -203,14 +147,20 @@ impl<Func> State<Func>
   }
 
   fn score(&self) -> i32 {
-    1000 * (self.y + 1) + 4 * (self.x + 1) + self.facing as i32
+    let facing = match self.facing {
+      Dir4::East => 0,
+      Dir4::South => 1,
+      Dir4::West => 2,
+      Dir4::North => 3,
+    };
+    1000 * (self.xy.y + 1) + 4 * (self.xy.x + 1) + facing
   }
 }
 
 fn part1_void_jump(src: &VoidConnection, map: &Map) -> VoidConnection {
-  let mut next = map.wrap_locations(src.facing.advance(src.xy));
-  while map.get(next.0, next.1) == Spot::Void {
-    next = map.wrap_locations(src.facing.advance(next));
+  let mut next = map.wrap_locations(src.xy + src.facing.delta());
+  while map.get(next) == Spot::Void {
+    next = map.wrap_locations(next + src.facing.delta());
   }
   VoidConnection{xy: next, facing: src.facing}
 }
@@ -228,12 +178,12 @@ pub fn part1(input: &InputType) -> OutputType {
 struct EdgeIterator<'a> {
   cube: &'a Cube<'a>,
   /// x-y in the map
-  map_xy: (i32, i32),
+  map_xy: Point,
   /// the direction we are currently moving
-  moving: Direction,
+  moving: Dir4,
   /// The edge that we are following.
   /// It is always 90 degrees from the moving direction.
-  following: Direction,
+  following: Dir4,
   /// Did we just make a turn?
   made_turn: bool,
 }
@@ -241,20 +191,20 @@ struct EdgeIterator<'a> {
 impl<'a> EdgeIterator<'a> {
   fn next(&mut self) -> Option<VoidConnection> {
     let result = VoidConnection{xy: self.map_xy, facing: self.following.flip()};
-    let (x,y) = self.moving.advance(self.map_xy);
-    let following = self.following.advance((x, y));
+    let ahead = self.map_xy + self.moving.delta();
+    let following = ahead + self.following.delta();
     // Do we need to turn?
-    if self.cube.map.get(following.0, following.1) == Spot::Void {
+    if self.cube.map.get(following) == Spot::Void {
       self.made_turn = true;
       (self.moving, self.following) = (self.following, self.moving.flip());
       self.map_xy = following;
-    } else if self.cube.map.get(x, y) != Spot::Void {
+    } else if self.cube.map.get(ahead) != Spot::Void {
       // Have we reached an inside turn? If so, we've lapped the cube and should stop.
       return None
     } else {
       // Otherwise, we are just continuing straight
       self.made_turn = false;
-      self.map_xy = (x, y);
+      self.map_xy = ahead;
     }
     Some(result)
   }
@@ -277,20 +227,20 @@ struct JoinIterator<'a> {
 }
 
 impl<'a> JoinIterator<'a> {
-  fn new(cube: &'a Cube, xy: (i32, i32), vert: Direction, horiz: Direction) -> Self {
-    let x = if horiz == Direction::Left {
+  fn new(cube: &'a Cube, xy: (i32, i32), vert: Dir4, horiz: Dir4) -> Self {
+    let x = if horiz == Dir4::West {
       xy.0 * cube.face_size
     } else {
       (xy.0 + 1) * cube.face_size - 1
     };
-    let y = if vert == Direction::Up {
+    let y = if vert == Dir4::North {
       xy.1 * cube.face_size
     } else {
       (xy.1 + 1) * cube.face_size - 1
     };
-    let h_itr = EdgeIterator{cube, map_xy: (x, y),
+    let h_itr = EdgeIterator{cube, map_xy: Point::new(x, y),
       moving: horiz.flip(), following: vert, made_turn: false};
-    let v_itr = EdgeIterator{cube, map_xy: (x, y),
+    let v_itr = EdgeIterator{cube, map_xy: Point::new(x, y),
       moving: vert.flip(), following: horiz, made_turn: false};
     JoinIterator{left: h_itr, right: v_itr, is_done: false}
   }
@@ -312,60 +262,47 @@ impl<'a> JoinIterator<'a> {
 struct Cube<'a> {
   map: &'a Map,
   // a map of where on the flattened map is an edge
-  overview: Vec<Vec<bool>>,
-  overview_size: (i32, i32),
+  overview: Grid<bool>,
   face_size: i32,
 }
 
 impl<'a> Cube<'a> {
   fn new(map: &'a Map) -> Self {
-    let mut overview = Vec::new();
-    for y in (0..map.size.1).step_by(map.face_size as usize) {
-      let mut row = Vec::new();
-      for x in (0..map.size.0).step_by(map.face_size as usize) {
-        if map.get(x,y) != Spot::Void {
-          row.push(true);
-        } else {
-          row.push(false);
-        }
-      }
-      overview.push(row);
-    }
-    Cube{map, overview, face_size: map.face_size,
-      overview_size: (map.size.0/map.face_size, map.size.1/map.face_size)}
+    let face_size = map.face_size as usize;
+    let overview = Grid::from_fn(map.walls.width() / face_size,
+                                 map.walls.height() / face_size,
+                                 |(x, y)| map.walls[(x * face_size, y * face_size)] != Spot::Void);
+    Cube{map, overview, face_size: map.face_size}
   }
 
   /// Get the face at the given position in the overview if one is there.
   fn get(&self, x: i32, y:i32) -> bool {
-    if (0..self.overview_size.0).contains(&x) && (0..self.overview_size.1).contains(&y) {
-      return self.overview[y as usize][x as usize]
-    }
-    false
+    self.overview.get(x, y).copied().unwrap_or(false)
   }
 
   /// Find all of the inside corners in the overview, because those represent the ends of the
   /// cuts that flattened the cube. Return a list of JoinIterators that will cover the
   /// perimeter.
-  fn find_inside_corners(&self) -> Vec<JoinIterator> {
+  fn find_inside_corners(&self) -> Vec<JoinIterator<'_>> {
     let mut result = Vec::new();
-    for y in 0..self.overview_size.1 {
-      for x in 0..self.overview_size.0 {
+    for y in 0..self.overview.height() as i32 {
+      for x in 0..self.overview.width() as i32 {
         if !self.get(x, y) {
           if self.get(x, y - 1) {
             if self.get(x - 1, y) {
               result.push(JoinIterator::new(self, (x, y),
-                                         Direction::Up, Direction::Left));
+                                         Dir4::North, Dir4::West));
             } else if self.get(x + 1, y) {
               result.push(JoinIterator::new(self, (x, y),
-                                         Direction::Up, Direction::Right));
+                                         Dir4::North, Dir4::East));
             }
           } else if self.get(x, y + 1) {
             if self.get(x - 1, y) {
               result.push(JoinIterator::new(self, (x, y),
-                                         Direction::Down, Direction::Left));
+                                         Dir4::South, Dir4::West));
             } else if self.get(x + 1, y) {
               result.push(JoinIterator::new(self, (x, y),
-                                         Direction::Down, Direction::Right));
+                                         Dir4::South, Dir4::East));
             }
           }
         }
@@ -380,11 +317,11 @@ impl<'a> Cube<'a> {
       while let Some((left, right)) = start.next() {
         let left_target = right.facing.flip();
         result.insert(left.clone(),
-                      VoidConnection{xy: left_target.advance(right.xy),
+                      VoidConnection{xy: right.xy + left_target.delta(),
                         facing: left_target});
         let right_target = left.facing.flip();
         result.insert(right.clone(),
-                      VoidConnection{xy: right_target.advance(left.xy),
+                      VoidConnection{xy: left.xy + right_target.delta(),
                         facing: right_target});
       }
     }
@@ -1,46 +1,18 @@
-use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
-
-#[derive(Clone,Copy,Debug,EnumIter)]
-enum Direction {
-  NorthWest,
-  North,
-  NorthEast,
-  West,
-  East,
-  SouthWest,
-  South,
-  SouthEast,
-}
+use serde::Serialize;
+use crate::utils::automaton::{Automaton, Moore, Sparse, Storage, Update};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::grid::Grid;
+use crate::utils::render::{self, Frame};
+use crate::utils::simulation::Simulation;
 
 /// Represent coordinates as i32.
 type Coordinate = i32;
 
-#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
-struct Position {
-  x: Coordinate,
-  y: Coordinate,
-}
-
-impl Position {
-  fn advance(&self, toward: Direction) -> Position {
-    match toward {
-      Direction::NorthWest => Position{x: self.x - 1, y: self.y - 1},
-      Direction::North => Position{x: self.x, y: self.y - 1},
-      Direction::NorthEast => Position{x: self.x + 1, y: self.y - 1},
-      Direction::East => Position{x: self.x + 1, y: self.y},
-      Direction::SouthEast => Position{x: self.x + 1, y: self.y + 1},
-      Direction::South => Position{x: self.x, y: self.y + 1},
-      Direction::SouthWest => Position{x: self.x - 1, y: self.y + 1},
-      Direction::West => Position{x: self.x - 1, y: self.y},
-    }
-  }
-}
+type Position = Point2<Coordinate>;
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct InputType {
   elves: Vec<Position>,
 }
@@ -51,92 +23,71 @@ pub fn generator(input: &str) -> InputType {
   let elves = input.lines().enumerate()
     .flat_map(|(line_num, line) | line.chars().enumerate()
       .filter(|(_, ch)| *ch == '#')
-      .map(move |(column, _)| Position{x: column as Coordinate, y: line_num as Coordinate}))
+      .map(move |(column, _)| Position::new(column as Coordinate, line_num as Coordinate)))
     .collect();
   InputType{elves}
 }
 
+/// The elves move around a sparse world, looking at all eight cells around
+/// them and moving together.
+type Elves = Automaton<Sparse<bool>, Moore>;
+
 #[derive(Debug)]
 struct State {
-  elves: Vec<Position>,
+  elves: Elves,
   first_rule: usize,
-  turn: usize,
 }
 
 impl State {
   const NUM_RULES: usize = 4;
   fn new(input: &InputType) -> Self {
-    State{elves: input.elves.clone(), first_rule: 0, turn: 0}
+    let cells = input.elves.iter().map(|&elf| (elf, true)).collect();
+    State{elves: Automaton::new(cells, Moore, Update::Synchronous), first_rule: 0}
   }
 
-  fn update_mask(mask: &mut u8, elf: Position, dir: Direction, locations: &HashSet<Position>) {
-    if locations.contains(&elf.advance(dir)) {
-      *mask |= 1 << (dir as usize);
-    }
+  fn turn(&self) -> usize {
+    self.elves.generation()
   }
 
-  fn find_rule(&self, elf: Position, locations: &HashSet<Position>) -> Option<Direction> {
-    let mut neighbors: u8 = 0;
-    for dir in Direction::iter() {
-      Self::update_mask(&mut neighbors, elf, dir, locations);
-    }
+  /// Pick the direction the elf wants to move given which of its neighbors,
+  /// in `Dir8` order, are elves.
+  fn find_rule(first_rule: usize, neighbors: &[bool]) -> Option<Dir4> {
+    let neighbors: u8 = neighbors.iter().enumerate()
+      .fold(0, |mask, (dir, &elf)| if elf { mask | 1 << dir } else { mask });
     if neighbors == 0 {
       return None;
     }
+    // The bits are the Dir8 values, so North is 0x01 and NorthWest is 0x80.
     for rule in 0..Self::NUM_RULES {
-      match (rule + self.first_rule) % Self::NUM_RULES {
-        0 => if neighbors & 0x7 == 0 { return Some(Direction::North); },
-        1 => if neighbors & 0xe0 == 0 { return Some(Direction::South); },
-        2 => if neighbors & 0x29 == 0 { return Some(Direction::West); },
-        3 => if neighbors & 0x94 == 0 { return Some(Direction::East); },
+      match (rule + first_rule) % Self::NUM_RULES {
+        0 => if neighbors & 0x83 == 0 { return Some(Dir4::North); },
+        1 => if neighbors & 0x38 == 0 { return Some(Dir4::South); },
+        2 => if neighbors & 0xe0 == 0 { return Some(Dir4::West); },
+        3 => if neighbors & 0x0e == 0 { return Some(Dir4::East); },
         x => panic!("Invalid rule number {}", x),
       }
     }
     None
   }
 
+  /// Every elf proposes a move, and the ones that nobody else proposed
+  /// happen. Returns whether any elf moved.
   fn everybody_move(&mut self) -> bool {
-    let num_elves = self.elves.len();
-    let locations = self.elves.iter().cloned().collect::<HashSet<Position>>();
-    let mut result = false;
-    // Generate all of the proposals with a count for each location
-    let mut proposals: HashMap<Position, usize> = HashMap::with_capacity(num_elves);
-    let mut elf_proposal: Vec<Option<Direction>> = Vec::with_capacity(num_elves);
-    for elf in &self.elves {
-      let rule = self.find_rule(*elf, &locations);
-      elf_proposal.push(rule);
-      if let Some(dir) = rule {
-        let new_loc = elf.advance(dir);
-        if let Some(cnt) = proposals.get_mut(&new_loc) {
-          *cnt += 1;
-        } else {
-          proposals.insert(new_loc, 1);
-        }
-      }
-    }
-    // If only 1 elf proposed moving there, go ahead and move
-    for (i, elf) in self.elves.iter_mut().enumerate() {
-      if let Some(dir) = elf_proposal[i] {
-        let new_loc = elf.advance(dir);
-        if *proposals.get(&new_loc).unwrap() == 1 {
-          result = true;
-          *elf = new_loc;
-        }
-      }
-    }
+    let first_rule = self.first_rule;
+    let result = self.elves.step_moves(|elf, _, neighbors|
+      Self::find_rule(first_rule, neighbors).map(|dir| elf + dir.delta()));
     self.first_rule = (self.first_rule + 1) % Self::NUM_RULES;
-    self.turn += 1;
     result
   }
 
   /// Find the x and y ranges of the positions.
   fn find_range(&self) -> (Range<Coordinate>, Range<Coordinate>) {
-    if self.elves.is_empty() {
+    if self.elves.cells().is_empty() {
       return (0..0, 0..0);
     }
     let mut x_range = (Coordinate::MAX, Coordinate::MIN);
     let mut y_range = (Coordinate::MAX, Coordinate::MIN);
-    for p in &self.elves {
+    for (p, _) in self.elves.cells().live() {
       x_range.0 = x_range.0.min(p.x);
       x_range.1 = x_range.1.max(p.x + 1);
       y_range.0 = y_range.0.min(p.y);
@@ -145,6 +96,16 @@ impl State {
     (x_range.0..x_range.1, y_range.0..y_range.1)
   }
 
+  /// Draw the elves in the area that they cover.
+  fn draw(&self) -> Frame {
+    let (x_range, y_range) = self.find_range();
+    let mut grid = Grid::new(x_range.len(), y_range.len(), '.');
+    for (p, _) in self.elves.cells().live() {
+      grid[((p.x - x_range.start) as usize, (p.y - y_range.start) as usize)] = '#';
+    }
+    grid
+  }
+
   /// Find the total size of the covered area
   fn find_size(&self) -> OutputType {
     let (x_range, y_range) = self.find_range();
@@ -155,27 +116,45 @@ impl State {
 impl Display for State {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     let (x_range, y_range) = self.find_range();
-    writeln!(f, "turn: {}, x: {x_range:?}, y: {y_range:?}", self.turn)?;
-    let locs = self.elves.iter().cloned().collect::<HashSet<Position>>();
-    for y in y_range.clone() {
-      for x in x_range.clone() {
-        if locs.contains(&Position{x,y}) {
-          write!(f, "#")?;
-        } else {
-          write!(f, ".")?;
-        }
-      }
-      writeln!(f)?;
-    }
-    Ok(())
+    writeln!(f, "turn: {}, x: {x_range:?}, y: {y_range:?}", self.turn())?;
+    write!(f, "{}", self.draw())
   }
 }
 const NUM_TURNS: usize = 10;
 
+/// The elves spreading out one round at a time.
+pub struct Rounds {
+  state: State,
+  /// Stop after this many rounds (part 1) instead of when the elves stop.
+  limit: Option<usize>,
+  settled: bool,
+}
+
+impl Rounds {
+  pub fn new(input: &InputType, part: usize) -> Self {
+    Rounds{state: State::new(input), limit: (part == 1).then_some(NUM_TURNS), settled: false}
+  }
+}
+
+impl Simulation for Rounds {
+  fn step(&mut self) {
+    self.settled = !self.state.everybody_move();
+  }
+
+  fn is_done(&self) -> bool {
+    self.settled || self.limit.is_some_and(|limit| self.state.turn() >= limit)
+  }
+
+  fn describe(&self) -> String {
+    self.state.to_string()
+  }
+}
+
 pub fn part1(input: &InputType) -> OutputType {
   let mut state = State::new(input);
   for _ in 0..NUM_TURNS {
     state.everybody_move();
+    render::frame(|| state.draw());
   }
   state.find_size() - input.elves.len() as OutputType
 }
@@ -183,14 +162,15 @@ pub fn part1(input: &InputType) -> OutputType {
 pub fn part2(input: &InputType) -> OutputType {
   let mut state = State::new(input);
   while state.everybody_move() {
-    // nothing
+    render::frame(|| state.draw());
   }
-  state.turn as OutputType
+  state.turn() as OutputType
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day23::{generator, part1, part2};
+  use crate::day23::{generator, part1, part2, Rounds};
+  use crate::utils::simulation::Simulation;
 
   #[test]
   fn test_tiny() {
@@ -208,6 +188,18 @@ mod tests {
     assert_eq!(110, part1(&input));
   }
 
+  #[test]
+  fn test_rounds() {
+    let input = generator("##\n#.\n..\n##");
+    let mut rounds = Rounds::new(&input, 2);
+    rounds.step();
+    assert_eq!("turn: 1, x: 0..2, y: -1..4\n##\n..\n#.\n.#\n#.\n", rounds.describe());
+    while !rounds.is_done() {
+      rounds.step();
+    }
+    assert!(rounds.describe().starts_with("turn: 4,"));
+  }
+
   #[test]
   fn test_part2() {
     assert_eq!(20, part2(&generator(INPUT)));
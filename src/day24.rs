@@ -1,54 +1,41 @@
 use std::collections::HashSet;
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use serde::Serialize;
+use crate::utils::automaton::{Automaton, Dense, Storage, Torus, Update, VonNeumann};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::grid::Grid;
+use crate::utils::render::{self, Frame};
+use crate::utils::search::{self, Graph, Path};
+use crate::utils::simulation::Simulation;
 
-#[derive(Clone,Copy,Debug,EnumIter,Eq,PartialEq)]
-enum Direction {
-  Still,
-  North,
-  West,
-  East,
-  South,
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
+enum Cell {
+  Open,
+  Wall,
+  Blizzard(Dir4),
 }
 
-impl Direction {
-  fn parse(ch: char) -> Option<Self> {
+impl Cell {
+  fn parse(ch: char) -> Self {
     match ch {
-      '^' => Some(Direction::North),
-      '<' => Some(Direction::West),
-      '>' => Some(Direction::East),
-      'v' => Some(Direction::South),
-      '#' => Some(Direction::Still),
-      '.' => None,
+      '^' => Cell::Blizzard(Dir4::North),
+      '<' => Cell::Blizzard(Dir4::West),
+      '>' => Cell::Blizzard(Dir4::East),
+      'v' => Cell::Blizzard(Dir4::South),
+      '#' => Cell::Wall,
+      '.' => Cell::Open,
       _ => panic!("Can't parse '{ch}'"),
     }
   }
 }
 
-/// Represent coordinates as i16.
-type Coordinate = i16;
+/// Represent coordinates as i32 to match the automaton.
+type Coordinate = i32;
 
-#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
-struct Position {
-  x: Coordinate,
-  y: Coordinate,
-}
+type Position = Point2<Coordinate>;
 
-impl Position {
-  fn advance(&self, dir: Direction) -> Self {
-    match dir {
-      Direction::Still => *self,
-      Direction::North => Position{x: self.x, y: self.y - 1},
-      Direction::South => Position{x: self.x, y: self.y + 1},
-      Direction::West => Position{x: self.x - 1, y: self.y},
-      Direction::East => Position{x: self.x + 1, y: self.y},
-    }
-  }
-}
-
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct InputType {
-  obstacles: Vec<Vec<Option<Direction>>>,
+  obstacles: Grid<Cell>,
   size: Position,
   start: Position,
   end: Position,
@@ -56,132 +43,237 @@ pub struct InputType {
 }
 
 impl InputType {
-  ///
-  fn get(&self, pos: Position) -> Option<Direction> {
-    if (0..self.size.x).contains(&pos.x) && (0..self.size.y).contains(&pos.y) {
-      return self.obstacles[pos.y as usize][pos.x as usize];
-    }
-    Some(Direction::Still)
+  /// Get the obstacle at the given position. Everything outside the canyon is a wall.
+  fn get(&self, pos: Position) -> Cell {
+    self.obstacles.get(pos.x, pos.y).copied().unwrap_or(Cell::Wall)
   }
 
-  /// Wrap positions around within the canyon.
-  fn normalize(&self, pos: Position) -> Position {
-    Position{x: (pos.x - 1).rem_euclid(self.size.x - 2) + 1,
-      y: (pos.y - 1).rem_euclid(self.size.y - 2) + 1}
+  /// Is the position inside the walls, where the blizzards blow?
+  fn is_inside(&self, pos: Position) -> bool {
+    pos.x > 0 && pos.x < self.size.x - 1 && pos.y > 0 && pos.y < self.size.y - 1
   }
+}
 
-  /// Is the given location and time safe from an obstacle moving in the given direction.
-  fn is_safe_specific(&self, pos: Position, time: usize, dir: Direction) -> bool {
-    let delta = time as Coordinate;
-    let adjusted_loc = match dir {
-      Direction::Still => pos,
-      Direction::West => self.normalize(Position{x: pos.x + delta, y: pos.y}),
-      Direction::East => self.normalize(Position{x: pos.x - delta, y: pos.y}),
-      Direction::North => self.normalize(Position{x: pos.x, y: pos.y + delta}),
-      Direction::South => self.normalize(Position{x: pos.x, y: pos.y - delta}),
-    };
-    let result = self.get(adjusted_loc);
-    //println!(" is_safe_specific {pos:?} at {time} for {dir:?} = {adjusted_loc:?} ({result:?})");
-    result != Some(dir)
+/// The blizzards in each cell inside the walls, with a bit for each
+/// direction.
+type Blizzards = Dense<u8>;
+
+fn blizzard_bit(dir: Dir4) -> u8 {
+  1 << dir as u8
+}
+
+/// Each blizzard moves into the next cell in its direction, wrapping around
+/// the canyon, so a cell gets the blizzards of its neighbors that point at it.
+fn blow(_: Position, _: u8, neighbors: &[u8]) -> u8 {
+  Dir4::ALL.iter()
+    .filter(|&&dir| neighbors[dir.flip() as usize] & blizzard_bit(dir) != 0)
+    .fold(0, |cell, &dir| cell | blizzard_bit(dir))
+}
+
+/// The canyon with the blizzards at every minute. The blizzards blowing
+/// east and west never meet the ones blowing north and south, so they are
+/// run separately, which only takes the width and the height of the canyon
+/// in minutes before they each repeat.
+pub struct Valley<'a> {
+  input: &'a InputType,
+  /// The east and west blizzards inside the walls at each minute, so (0, 0)
+  /// is the canyon's (1, 1).
+  across: Vec<Grid<u8>>,
+  /// The north and south blizzards at each minute.
+  up_down: Vec<Grid<u8>>,
+}
+
+impl<'a> Valley<'a> {
+  pub fn new(input: &'a InputType) -> Self {
+    let (width, height) = (input.size.x as usize - 2, input.size.y as usize - 2);
+    Valley{input, across: Self::blow_for(input, &[Dir4::East, Dir4::West], width),
+           up_down: Self::blow_for(input, &[Dir4::North, Dir4::South], height)}
+  }
+
+  /// Run the blizzards going in the given directions for the given number
+  /// of minutes.
+  fn blow_for(input: &InputType, dirs: &[Dir4], minutes: usize) -> Vec<Grid<u8>> {
+    let origin = Position::new(1, 1);
+    let (width, height) = (input.size.x as usize - 2, input.size.y as usize - 2);
+    let mut blizzards = Blizzards::new(origin, width, height);
+    for (pos, cell) in input.obstacles.iter() {
+      if let Cell::Blizzard(dir) = cell {
+        if dirs.contains(dir) {
+          let pos = Position::new(pos.0 as Coordinate, pos.1 as Coordinate);
+          blizzards.set(pos, blizzards.get(pos) | blizzard_bit(*dir));
+        }
+      }
+    }
+    let mut weather = Automaton::new(blizzards,
+      Torus::new(VonNeumann, origin, width, height), Update::Synchronous);
+    let mut history = Vec::with_capacity(minutes);
+    history.push(weather.cells().grid().clone());
+    while history.len() < minutes {
+      weather.step(blow);
+      history.push(weather.cells().grid().clone());
+    }
+    history
+  }
+
+  /// Get the blizzards in the cell inside the walls at the given time.
+  fn blizzards(&self, pos: Position, time: usize) -> u8 {
+    let pos = (pos.x as usize - 1, pos.y as usize - 1);
+    self.across[time % self.across.len()][pos] | self.up_down[time % self.up_down.len()][pos]
   }
 
   /// Is the given position safe at the given time?
   fn is_safe(&self, pos: Position, time: usize) -> bool {
-    if pos.x <= 0 || pos.x >= self.size.x - 1 || pos.y <= 0 || pos.y >= self.size.y - 1 {
-      return self.get(pos).is_none();
+    if self.input.is_inside(pos) {
+      self.blizzards(pos, time) == 0
+    } else {
+      self.input.get(pos) == Cell::Open
     }
-    // Where in the cycle are we?
-    let cycle = time % self.period;
-    for dir in Direction::iter() {
-      if !self.is_safe_specific(pos, cycle, dir) {
-        return false;
+  }
+
+  /// Draw the blizzards at the given time and the positions the expedition
+  /// could be in. Spots with several blizzards show how many there are.
+  fn draw(&self, time: usize, expedition: &[Position]) -> Frame {
+    let mut frame = self.input.obstacles.map(|cell| if *cell == Cell::Wall { '#' } else { '.' });
+    for pos in self.input.obstacles.positions() {
+      let pos_2d = Position::new(pos.0 as Coordinate, pos.1 as Coordinate);
+      if !self.input.is_inside(pos_2d) {
+        continue;
+      }
+      let blizzards = self.blizzards(pos_2d, time);
+      frame[pos] = match blizzards.count_ones() {
+        0 => frame[pos],
+        1 => match Dir4::ALL[blizzards.trailing_zeros() as usize] {
+          Dir4::North => '^',
+          Dir4::East => '>',
+          Dir4::South => 'v',
+          Dir4::West => '<',
+        },
+        n => char::from_digit(n, 10).unwrap(),
       }
     }
-    true
+    for pos in expedition {
+      frame[(pos.x as usize, pos.y as usize)] = 'E';
+    }
+    frame
   }
 }
 
 type OutputType = usize;
 
+fn gcd(a: usize, b: usize) -> usize {
+  if b == 0 { a } else { gcd(b, a % b) }
+}
+
 pub fn generator(input: &str) -> InputType {
-  let obstacles: Vec<Vec<Option<Direction>>> = input.lines()
-    .map(|l| l.chars().map(Direction::parse).collect())
-    .collect();
-  let start = Position{y: 0,
-    x: obstacles[0].iter().enumerate()
-      .find(|(_, &o)| o.is_none()).unwrap().0 as Coordinate};
-  let rows = obstacles.len();
-  let end = Position{y: (rows - 1) as Coordinate,
-    x: obstacles[rows - 1].iter().enumerate()
-      .find(|(_, o)| o.is_none()).unwrap().0 as Coordinate};
-  let width = obstacles.iter().map(|r| r.len()).max().unwrap_or(0);
-  let size = Position{x: width as Coordinate, y: rows as Coordinate};
-  // What is the time period that all of the obstacles repeat on?
-  // We use this so that we don't overflow the i16, even if this goes a long time.
-  let period = (width - 2) * (rows - 2);
+  let obstacles = Grid::parse(input, Cell::parse);
+  let start = Position::new(
+    obstacles.row(0).iter().position(|&c| c == Cell::Open).unwrap() as Coordinate, 0);
+  let rows = obstacles.height();
+  let end = Position::new(
+    obstacles.row(rows - 1).iter().position(|&c| c == Cell::Open).unwrap() as Coordinate,
+    (rows - 1) as Coordinate);
+  let width = obstacles.width();
+  let size = Position::new(width as Coordinate, rows as Coordinate);
+  // What is the time period that all of the obstacles repeat on? The ones
+  // going across repeat every width and the ones going up and down every
+  // height.
+  let (inside_width, inside_height) = (width - 2, rows - 2);
+  let period = inside_width / gcd(inside_width, inside_height) * inside_height;
   InputType{obstacles, start, end, size, period}
 }
 
-#[derive(Debug)]
-struct State<'a> {
-  input: &'a InputType,
-  turn: usize,
-  locations: HashSet<Position>,
+/// The search nodes are the position and the time within the blizzards' period.
+impl Graph for Valley<'_> {
+  type Node = (Position, usize);
+
+  fn neighbors(&self, &(loc, time): &(Position, usize)) -> impl Iterator<Item=(Position, usize)> {
+    let next_time = (time + 1) % self.input.period;
+    std::iter::once(loc).chain(Dir4::ALL.iter().map(move |dir| loc + dir.delta()))
+      .filter(move |&next| self.is_safe(next, next_time))
+      .map(move |next| (next, next_time))
+  }
 }
 
-impl<'a> State<'a> {
-  fn new(input: &'a InputType) -> Self {
-    let mut locations = HashSet::new();
-    locations.insert(input.start);
-    State{input, turn: 0, locations}
+/// Find how long it takes to get from start to goal, leaving at the given time.
+fn travel(valley: &Valley, start: Position, goal: Position, time: usize) -> OutputType {
+  // The search finishes each minute before starting the next, so draw the
+  // frontier when the minute changes.
+  let mut minute = time % valley.input.period;
+  let mut frontier = Vec::new();
+  search::bfs(valley, [(start, minute)], |&(loc, time)| {
+    if render::is_enabled() {
+      if time != minute {
+        render::frame(|| valley.draw(minute, &frontier));
+        frontier.clear();
+        minute = time;
+      }
+      frontier.push(loc);
+    }
+    loc == goal
+  }, Path::Skip).expect("no path through the blizzards").cost
+}
+
+/// Every place the expedition could be, moving forward a minute at a time.
+pub struct Expedition<'a> {
+  valley: Valley<'a>,
+  minute: usize,
+  frontier: HashSet<Position>,
+  /// The goals of the remaining trips, in reverse order.
+  goals: Vec<Position>,
+}
+
+impl<'a> Expedition<'a> {
+  pub fn new(input: &'a InputType, part: usize) -> Self {
+    let goals = if part == 2 { vec![input.end, input.start, input.end] } else { vec![input.end] };
+    Expedition{valley: Valley::new(input), minute: 0, frontier: HashSet::from([input.start]),
+               goals}
   }
+}
 
+impl Simulation for Expedition<'_> {
   fn step(&mut self) {
-    let mut next = HashSet::new();
-    for loc in &self.locations {
-      for dir in Direction::iter() {
-        next.insert(loc.advance(dir));
+    self.minute += 1;
+    let node_time = (self.minute - 1) % self.valley.input.period;
+    let mut next_frontier = HashSet::new();
+    for &loc in &self.frontier {
+      next_frontier.extend(self.valley.neighbors(&(loc, node_time)).map(|(next, _)| next));
+    }
+    self.frontier = next_frontier;
+    // Once a trip is finished, the next one starts from its goal.
+    if let Some(&goal) = self.goals.last() {
+      if self.frontier.contains(&goal) {
+        self.frontier = HashSet::from([goal]);
+        self.goals.pop();
       }
     }
-    next.retain(|loc| self.input.is_safe(*loc, self.turn + 1));
-    self.locations = next;
-    self.turn += 1;
   }
 
-  fn done(&self, goal: Position) -> bool {
-    self.locations.contains(&goal)
+  fn is_done(&self) -> bool {
+    self.goals.is_empty()
+  }
+
+  fn describe(&self) -> String {
+    let frontier: Vec<Position> = self.frontier.iter().copied().collect();
+    format!("minute {}: {} trips left, {} positions\n{}", self.minute, self.goals.len(),
+            frontier.len(), self.valley.draw(self.minute, &frontier))
   }
 }
 
 pub fn part1(input: &InputType) -> OutputType {
-  let mut state = State::new(input);
-  while !state.done(input.end) {
-    state.step();
-  }
-  state.turn
+  travel(&Valley::new(input), input.start, input.end, 0)
 }
 
 pub fn part2(input: &InputType) -> OutputType {
-  let mut state = State::new(input);
-  while !state.done(input.end) {
-    state.step();
-  }
-  state.locations.clear();
-  state.locations.insert(input.end);
-  while !state.done(input.start) {
-    state.step();
-  }
-  state.locations.clear();
-  state.locations.insert(input.start);
-  while !state.done(input.end) {
-    state.step();
-  }
-  state.turn
+  let valley = Valley::new(input);
+  let there = travel(&valley, input.start, input.end, 0);
+  let back = travel(&valley, input.end, input.start, there);
+  there + back + travel(&valley, input.start, input.end, there + back)
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day24::{generator, part1, part2};
+  use crate::day24::{generator, part1, part2, Expedition};
+  use crate::utils::simulation::Simulation;
 
   #[test]
   fn test_part1() {
@@ -189,6 +281,19 @@ mod tests {
     assert_eq!(18, part1(&input));
   }
 
+  #[test]
+  fn test_expedition() {
+    let input = generator(INPUT);
+    let mut expedition = Expedition::new(&input, 2);
+    expedition.step();
+    assert!(expedition.describe().starts_with("minute 1: 3 trips left, 2 positions\n\
+                                              #E######\n#E>3.<.#\n"));
+    while !expedition.is_done() {
+      expedition.step();
+    }
+    assert!(expedition.describe().starts_with("minute 54: 0 trips left,"));
+  }
+
   #[test]
   fn test_part2() {
     assert_eq!(54, part2(&generator(INPUT)));
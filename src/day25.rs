@@ -1,3 +1,5 @@
+use crate::utils::Answer;
+
 type InputType = Vec<String>;
 type OutputType = String;
 
@@ -57,8 +59,9 @@ pub fn part1(input: &InputType) -> OutputType {
   i64_to_snafu(input.iter().map(|l| snafu_to_i64(l)).sum())
 }
 
-pub fn part2(_input: &InputType) -> OutputType {
-  String::new()
+/// There is no second puzzle on the last day.
+pub fn part2(_input: &InputType) -> Answer {
+  Answer::None
 }
 
 #[cfg(test)]
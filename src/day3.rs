@@ -1,54 +1,107 @@
-/// Define a set of items using a long
-#[derive(Debug,Default)]
-pub struct Contents {
-  items: u64,
+use std::collections::HashMap;
+use std::fmt;
+use itertools::Itertools;
+use serde::{Serialize, Serializer};
+use crate::utils::anonymize::{Anonymized, AnswerMap, Rng};
+use crate::utils::bitset::BitSet;
+use crate::utils::params;
+
+/// The number of words in a set of items.
+const WORDS: usize = 4;
+
+/// The most item types an alphabet can have, which is enough for every byte.
+pub const CAPACITY: usize = BitSet::<WORDS>::CAPACITY;
+
+/// The kinds of items that can be in a rucksack, from the lowest priority
+/// to the highest.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Alphabet {
+  items: Vec<char>,
+  positions: HashMap<char, usize>,
 }
 
-impl Contents {
-  const SIZE: usize = 56;
+impl Alphabet {
+  pub fn new(items: impl IntoIterator<Item=char>) -> Result<Self, String> {
+    let items: Vec<char> = items.into_iter().collect();
+    if items.is_empty() {
+      return Err("the alphabet is empty".to_string())
+    }
+    if items.len() > CAPACITY {
+      return Err(format!("the alphabet has {} items, but the limit is {CAPACITY}",
+                         items.len()))
+    }
+    let mut positions = HashMap::new();
+    for (posn, &ch) in items.iter().enumerate() {
+      if positions.insert(ch, posn).is_some() {
+        return Err(format!("the alphabet repeats {ch:?}"))
+      }
+    }
+    Ok(Alphabet{items, positions})
+  }
 
-  fn set(&mut self, posn: usize) {
-    self.items |= 1u64 << posn;
+  /// The puzzle's items: a-z and then A-Z.
+  pub fn letters() -> Self {
+    Self::new(('a'..='z').chain('A'..='Z')).unwrap()
   }
 
-  /// Parse from a list o characters
-  fn from_chars(chars: &[char]) -> Self {
-    let mut result = Self::default();
-    for ch in chars {
-      result.set(Self::position(*ch));
-    }
-    result
+  /// Every byte, as the char with the same code.
+  pub fn bytes() -> Self {
+    Self::new((0..=u8::MAX).map(char::from)).unwrap()
   }
 
-  /// Map the characters to position in the array
-  fn position(ch: char) -> usize {
-    match ch {
-      'a'..='z' => (ch as usize) - ('a' as usize),
-      'A'..='Z' => (ch as usize) - ('A' as usize) + 26,
-      _ => panic!("Bad value {ch}")
-    }
+  /// The items in priority order.
+  pub fn items(&self) -> &[char] {
+    &self.items
+  }
+
+  /// Map the character to its position in the alphabet
+  pub fn position(&self, ch: char) -> Option<usize> {
+    self.positions.get(&ch).copied()
   }
 
   /// Map the position back into a character
-  fn item(pos: usize) -> char {
-    match pos {
-      0..=25 => char::from_u32(('a' as u32) + pos as u32).unwrap(),
-      26..=51 => char::from_u32(('A' as u32) + pos as u32 - 26).unwrap(),
-      _ => panic!("Bad position {pos}")
-    }
+  pub fn item(&self, posn: usize) -> char {
+    self.items[posn]
+  }
+}
+
+impl Serialize for Alphabet {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&self.items.iter().collect::<String>())
+  }
+}
+
+/// An item type and its priority.
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
+pub struct Item {
+  pub item: char,
+  pub priority: i32,
+}
+
+/// Define a set of items as a set of positions in the alphabet
+#[derive(Clone,Copy,Debug,Default,Eq,PartialEq,Serialize)]
+pub struct Contents {
+  items: BitSet<WORDS>,
+}
+
+impl FromIterator<usize> for Contents {
+  fn from_iter<T: IntoIterator<Item=usize>>(iter: T) -> Self {
+    Contents{items: iter.into_iter().collect()}
   }
+}
 
+impl Contents {
   /// Create the intersection of a list of sets
-  fn intersect(sets: &[Self]) -> Self {
-    let mut result = Contents{items: u64::MAX};
+  pub fn intersect(sets: &[Self]) -> Self {
+    let mut items = sets.first().map(|s| s.items).unwrap_or_default();
     for s in sets {
-      result.items &= s.items;
+      items &= s.items;
     }
-    result
+    Contents{items}
   }
 
-  /// Create the union oof a list of sets
-  fn union(sets: &[Self]) -> Self {
+  /// Create the union of a list of sets
+  pub fn union(sets: &[Self]) -> Self {
     let mut result = Self::default();
     for s in sets {
       result.items |= s.items;
@@ -56,69 +109,311 @@ impl Contents {
     result
   }
 
-  /// Return the first item in the set
-  fn first_item(&self) -> Option<char> {
-    let posn = self.items.trailing_zeros() as usize;
-    if posn <= Self::SIZE { Some(Self::item(posn)) } else { None }
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.items.is_empty()
   }
-}
 
-fn priority(ch: char) -> i32 {
-  Contents::position(ch) as i32 + 1
+  /// The items in the set, from the lowest priority up.
+  pub fn items<'a>(&'a self, alphabet: &'a Alphabet) -> impl Iterator<Item=Item> + 'a {
+    self.items.iter().map(|posn| Item{item: alphabet.item(posn), priority: posn as i32 + 1})
+  }
 }
 
 /// Define Rucksacks as lists of sets
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Rucksack {
   parts: Vec<Contents>,
 }
 
 impl Rucksack {
+  /// Parse a rucksack from a string, dividing it evenly into the
+  /// compartments.
+  pub fn parse(s: &str, alphabet: &Alphabet, compartments: usize) -> Result<Self, String> {
+    let positions: Vec<usize> = s.chars().enumerate()
+      .map(|(column, ch)| alphabet.position(ch)
+        .ok_or_else(|| format!("unknown item {ch:?} in column {}", column + 1)))
+      .collect::<Result<_, _>>()?;
+    if positions.is_empty() || compartments == 0 || !positions.len().is_multiple_of(compartments) {
+      return Err(format!("can't split {} items into {compartments} compartments",
+                         positions.len()))
+    }
+    let parts = positions.chunks(positions.len() / compartments)
+      .map(|chunk| chunk.iter().copied().collect()).collect();
+    Ok(Rucksack{parts})
+  }
 
-  /// Parse a rucksack from a string.
-  /// Divides the string in half to get two compartments.
-  fn from_str(s: &str) -> Self {
-    let chars: Vec<char> = s.chars().collect();
-    assert_eq!(chars.len() % 2, 0);
-    let parts = chars.chunks(chars.len() / 2)
-      .map(Contents::from_chars).collect();
-    Rucksack{parts}
+  pub fn compartments(&self) -> &[Contents] {
+    &self.parts
   }
 
-  /// Find the common item between the compartments
-  fn find_match(&self) -> Option<char> {
-    Contents::intersect(&self.parts).first_item()
+  /// Find the items that are in every compartment
+  pub fn shared(&self) -> Contents {
+    Contents::intersect(&self.parts)
   }
 
   /// Get the set of all items in the rucksack.
-  fn items(&self) -> Contents {
+  pub fn items(&self) -> Contents {
     Contents::union(&self.parts)
   }
 }
 
-/// Find the common item in a group of rucksacks
-fn find_match(group: &[Rucksack]) -> Option<char> {
+/// Find the items that are in every rucksack of a group
+pub fn shared_in_group(group: &[Rucksack]) -> Contents {
   let contents: Vec<Contents> = group.iter().map(|r| r.items()).collect();
-  Contents::intersect(&contents).first_item()
+  Contents::intersect(&contents)
+}
+
+/// The rucksacks and the alphabet of their items.
+#[derive(Debug,Serialize)]
+pub struct Inventory {
+  pub alphabet: Alphabet,
+  pub rucksacks: Vec<Rucksack>,
+}
+
+impl Inventory {
+  /// Parse one rucksack per a line, with the line number in any error.
+  pub fn parse(input: &str, alphabet: Alphabet, compartments: usize) -> Result<Self, String> {
+    let rucksacks = input.lines().enumerate()
+      .map(|(line, s)| Rucksack::parse(s, &alphabet, compartments)
+        .map_err(|e| format!("line {}: {e}", line + 1)))
+      .collect::<Result<_, _>>()?;
+    Ok(Inventory{alphabet, rucksacks})
+  }
+}
+
+/// The number of compartments in a rucksack, which the runner can change
+/// with the `day3.compartments` parameter.
+const DEFAULT_COMPARTMENTS: usize = 2;
+
+/// The number of elves in a group, which is the `day3.group` parameter.
+const DEFAULT_GROUP: usize = 3;
+
+/// The items are the letters unless the `day3.alphabet` parameter lists
+/// them instead.
+fn alphabet() -> Alphabet {
+  match params::get("day3.alphabet", String::new()) {
+    items if items.is_empty() => Alphabet::letters(),
+    items => Alphabet::new(items.chars())
+      .unwrap_or_else(|e| panic!("Bad day3.alphabet: {e}")),
+  }
+}
+
+fn compartments() -> usize {
+  params::get("day3.compartments", DEFAULT_COMPARTMENTS)
 }
 
-pub fn generator(input: &str) -> Vec<Rucksack> {
-  input.lines().map(Rucksack::from_str).collect()
+fn group_size() -> usize {
+  params::get("day3.group", DEFAULT_GROUP)
+}
+
+pub fn generator(input: &str) -> Inventory {
+  Inventory::parse(input, alphabet(), compartments())
+    .unwrap_or_else(|e| panic!("Can't parse rucksacks: {e}"))
+}
+
+/// The priority of the lowest priority item in the set, or a panic that
+/// names what didn't have a common item.
+fn first_priority(contents: Contents, alphabet: &Alphabet, what: impl Fn() -> String) -> i32 {
+  contents.items(alphabet).next()
+    .unwrap_or_else(|| panic!("{} has no common item", what())).priority
 }
 
 /// Find the common item in each sack and sum the priorities.
-pub fn part1(input: &[Rucksack]) -> i32 {
-  input.iter().map(|r| priority(r.find_match().unwrap())).sum()
+pub fn part1(input: &Inventory) -> i32 {
+  input.rucksacks.iter().enumerate()
+    .map(|(i, r)| first_priority(r.shared(), &input.alphabet,
+                                 || format!("rucksack {}", i + 1)))
+    .sum()
+}
+
+/// Group the sacks, find the common item, and sum the priorities.
+pub fn part2(input: &Inventory) -> i32 {
+  let size = group_size();
+  assert!(size > 0 && input.rucksacks.len().is_multiple_of(size),
+          "Can't split {} rucksacks into groups of {size}", input.rucksacks.len());
+  input.rucksacks.chunks(size).enumerate()
+    .map(|(i, group)| first_priority(shared_in_group(group), &input.alphabet,
+                                     || format!("group {}", i + 1)))
+    .sum()
+}
+
+/// The number of rucksacks in a group when recovering the groups.
+const BADGE_GROUP: usize = 3;
+
+/// A group of rucksacks, by their positions in the input, and its badge.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub struct BadgeGroup {
+  pub rucksacks: [usize; BADGE_GROUP],
+  pub badge: Item,
+}
+
+/// The ways of splitting the rucksacks into groups that each share exactly
+/// one item.
+#[derive(Clone,Debug,Eq,PartialEq,Serialize)]
+pub enum Badges {
+  /// The only way to split them.
+  Unique(Vec<BadgeGroup>),
+  /// Two of the ways to split them.
+  Several(Vec<BadgeGroup>, Vec<BadgeGroup>),
+  /// Why they can't be split.
+  Impossible(String),
+}
+
+/// Describe the groups by the input's line numbers, which start at 1.
+impl fmt::Display for Badges {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let groups = |groups: &[BadgeGroup]| groups.iter()
+      .map(|g| format!("{} {:?}", g.badge.item, g.rucksacks.map(|r| r + 1))).join(", ");
+    match self {
+      Badges::Unique(partition) => write!(f, "the only grouping is {}", groups(partition)),
+      Badges::Several(first, second) =>
+        write!(f, "there are several groupings, such as {} and {}",
+               groups(first), groups(second)),
+      Badges::Impossible(reason) => write!(f, "there is no grouping, because {reason}"),
+    }
+  }
+}
+
+/// The state of the search for the groups.
+struct BadgeSearch<'a> {
+  /// The possible groups with the position of their badge.
+  groups: &'a [([usize; BADGE_GROUP], usize)],
+  /// The possible groups that each rucksack is in.
+  by_rucksack: Vec<Vec<usize>>,
+  used: Vec<bool>,
+  chosen: Vec<usize>,
+  found: Vec<Vec<usize>>,
+}
+
+impl BadgeSearch<'_> {
+  fn is_open(&self, group: usize) -> bool {
+    self.groups[group].0.iter().all(|&r| !self.used[r])
+  }
+
+  /// Choose groups for the remaining rucksacks, starting with the one that
+  /// is in the fewest possible groups, until two ways have been found.
+  fn search(&mut self) {
+    let next = (0..self.used.len()).filter(|&r| !self.used[r])
+      .map(|r| (self.by_rucksack[r].iter().filter(|&&g| self.is_open(g)).count(), r))
+      .min();
+    let Some((_, rucksack)) = next else {
+      self.found.push(self.chosen.clone());
+      return
+    };
+    for i in 0..self.by_rucksack[rucksack].len() {
+      let group = self.by_rucksack[rucksack][i];
+      if !self.is_open(group) {
+        continue
+      }
+      for &r in &self.groups[group].0 {
+        self.used[r] = true;
+      }
+      self.chosen.push(group);
+      self.search();
+      self.chosen.pop();
+      for &r in &self.groups[group].0 {
+        self.used[r] = false;
+      }
+      if self.found.len() > 1 {
+        return
+      }
+    }
+  }
+}
+
+/// Split the rucksacks into groups of three that each share exactly one
+/// item, without assuming that a group's rucksacks are next to each other.
+pub fn recover_groups(input: &Inventory) -> Badges {
+  let items: Vec<Contents> = input.rucksacks.iter().map(|r| r.items()).collect();
+  let n = items.len();
+  if !n.is_multiple_of(BADGE_GROUP) {
+    return Badges::Impossible(format!("{n} rucksacks can't be split into groups of \
+                                       {BADGE_GROUP}"))
+  }
+  let mut groups = Vec::new();
+  for i in 0..n {
+    for j in i + 1..n {
+      let pair = Contents::intersect(&[items[i], items[j]]);
+      if pair.is_empty() {
+        continue
+      }
+      for (k, &third) in items.iter().enumerate().skip(j + 1) {
+        let shared = Contents::intersect(&[pair, third]);
+        if shared.len() == 1 {
+          groups.push(([i, j, k], shared.items.first().unwrap()));
+        }
+      }
+    }
+  }
+  let mut by_rucksack = vec![Vec::new(); n];
+  for (g, (members, _)) in groups.iter().enumerate() {
+    for &r in members {
+      by_rucksack[r].push(g);
+    }
+  }
+  if let Some(r) = by_rucksack.iter().position(|gs| gs.is_empty()) {
+    return Badges::Impossible(format!("the rucksack on line {} doesn't share exactly one \
+                                       item with any two others", r + 1))
+  }
+  let mut search = BadgeSearch{groups: &groups, by_rucksack, used: vec![false; n],
+                               chosen: Vec::new(), found: Vec::new()};
+  search.search();
+  let mut partitions = search.found.into_iter().map(|chosen| {
+    let mut partition: Vec<BadgeGroup> = chosen.into_iter().map(|g| {
+      let (rucksacks, badge) = groups[g];
+      BadgeGroup{rucksacks, badge: Item{item: input.alphabet.item(badge),
+                                        priority: badge as i32 + 1}}
+    }).collect();
+    partition.sort_by_key(|g| g.rucksacks);
+    partition
+  });
+  match (partitions.next(), partitions.next()) {
+    (Some(first), None) => Badges::Unique(first),
+    (Some(first), Some(second)) => Badges::Several(first, second),
+    _ => Badges::Impossible("every way of grouping some of the rucksacks leaves others \
+                             that can't be grouped".to_string()),
+  }
 }
 
-/// Group the sacks into sets of 3, find the common item, and sum the priorities.
-pub fn part2(input: &[Rucksack]) -> i32 {
-  input.chunks(3).map(|group| priority(find_match(group).unwrap())).sum()
+/// Permute the alphabet, shuffle the items within each compartment, and
+/// shuffle the groups, using the same parameters as the solver. The
+/// priorities of the common items change, so the answers have to be
+/// recomputed.
+pub fn anonymize(input: &str, rng: &mut Rng) -> Anonymized {
+  let alphabet = alphabet();
+  let compartments = compartments();
+  let mut items = alphabet.items().to_vec();
+  rng.shuffle(&mut items);
+  let lines: Vec<String> = input.lines().map(|line| {
+    let mut chars: Vec<char> = line.chars()
+      .map(|ch| alphabet.position(ch).map_or(ch, |posn| items[posn])).collect();
+    // the solver rejects rucksacks that don't split evenly, so leave them be
+    if compartments > 0 && chars.len().is_multiple_of(compartments) {
+      let size = (chars.len() / compartments).max(1);
+      for part in chars.chunks_mut(size) {
+        rng.shuffle(part);
+      }
+    }
+    chars.into_iter().collect()
+  }).collect();
+  let mut groups: Vec<&[String]> = lines.chunks(group_size().max(1)).collect();
+  rng.shuffle(&mut groups);
+  let text = groups.concat().iter().map(|l| format!("{l}\n")).collect();
+  Anonymized{text, answers: AnswerMap::Recompute}
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day3::{generator, part1, part2};
+  use std::collections::BTreeMap;
+  use itertools::Itertools;
+  use crate::day3::{anonymize, generator, part1, part2, recover_groups, shared_in_group,
+                    Alphabet, BadgeGroup, Badges, Contents, Inventory, Item, Rucksack};
+  use crate::utils::anonymize::Rng;
+  use crate::utils::params;
 
   const INPUT: &str = "vJrwpWtwJgWrhcsFMMfFFhFp\n\
                        jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\n\
@@ -127,6 +422,109 @@ mod tests {
                        ttgJtRGJQctTZtZT\n\
                        CrZsJsPPZsGzwwsLwLmpwMDw";
 
+  #[test]
+  fn test_anonymize() {
+    let anonymized = anonymize(INPUT, &mut Rng::new(3));
+    let mut lengths: Vec<usize> = anonymized.text.lines().map(|l| l.len()).collect();
+    lengths.sort();
+    assert_eq!(vec![16, 18, 24, 24, 30, 32], lengths);
+    // the letters are the first thing shuffled, so the same seed renames them the same way
+    let letters = Alphabet::letters();
+    let mut renamed = letters.items().to_vec();
+    Rng::new(3).shuffle(&mut renamed);
+    let rename = |ch: char| renamed[letters.position(ch).unwrap()];
+    // the groups are the same, up to the order of the groups and the order of the items
+    // in each compartment
+    let groups = |text: &str, rename: &dyn Fn(char) -> char| -> Vec<Vec<Vec<String>>> {
+      let lines: Vec<Vec<String>> = text.lines().map(|line| {
+        let chars: Vec<char> = line.chars().map(rename).collect();
+        chars.chunks(chars.len() / 2).map(|c| c.iter().sorted().collect()).collect()
+      }).collect();
+      lines.chunks(3).map(|group| group.to_vec()).sorted().collect()
+    };
+    assert_eq!(groups(INPUT, &rename), groups(&anonymized.text, &|ch| ch));
+    // so the answers are the priorities of the renamed common items
+    let original = generator(INPUT);
+    let priority = |contents: Contents| contents.items(&letters)
+      .map(|item| letters.position(rename(item.item)).unwrap() as i32 + 1).next().unwrap();
+    let input = generator(&anonymized.text);
+    assert_eq!(original.rucksacks.iter().map(|r| priority(r.shared())).sum::<i32>(),
+               part1(&input));
+    assert_eq!(original.rucksacks.chunks(3).map(|g| priority(shared_in_group(g))).sum::<i32>(),
+               part2(&input));
+  }
+
+  #[test]
+  fn test_alphabet() {
+    let letters = Alphabet::letters();
+    assert_eq!(Some(0), letters.position('a'));
+    assert_eq!(Some(51), letters.position('Z'));
+    assert_eq!(None, letters.position('!'));
+    assert_eq!('A', letters.item(26));
+    assert_eq!(Some(255), Alphabet::bytes().position('\u{ff}'));
+    assert_eq!(Err("the alphabet repeats 'x'".to_string()), Alphabet::new("xyx".chars()));
+    assert!(Alphabet::new(std::iter::empty()).is_err());
+    assert!(Alphabet::new((0..300).filter_map(char::from_u32)).is_err());
+  }
+
+  #[test]
+  fn parsing_test() {
+    let letters = Alphabet::letters();
+    assert_eq!(Err("line 2: unknown item '!' in column 3".to_string()),
+               Inventory::parse("abab\nab!b\n", letters.clone(), 2).map(|_| ()));
+    assert_eq!(Err("line 1: can't split 5 items into 2 compartments".to_string()),
+               Inventory::parse("abcab\n", letters.clone(), 2).map(|_| ()));
+    let sack = Rucksack::parse("abcacbabd", &letters, 3).unwrap();
+    assert_eq!(3, sack.compartments().len());
+    assert_eq!(vec![Item{item: 'a', priority: 1}, Item{item: 'b', priority: 2}],
+               sack.shared().items(&letters).collect::<Vec<_>>());
+    assert_eq!(4, sack.items().len());
+    let digits = Alphabet::new("0123456789".chars()).unwrap();
+    let group = Inventory::parse("1293\n3945\n0399\n", digits.clone(), 2).unwrap();
+    assert_eq!(vec![Item{item: '3', priority: 4}, Item{item: '9', priority: 10}],
+               shared_in_group(&group.rucksacks).items(&digits).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_params() {
+    params::install(BTreeMap::from([("day3.alphabet".to_string(), "xyz".to_string()),
+                                    ("day3.compartments".to_string(), "3".to_string()),
+                                    ("day3.group".to_string(), "2".to_string())]));
+    let input = generator("xyyzzy\nzzz\nxzzxzx\nxxx");
+    assert_eq!(2 + 3 + 1 + 1, part1(&input));
+    assert_eq!(3 + 1, part2(&input));
+    // the anonymizer keeps the items in their compartments and groups, so
+    // the same number of items are shared
+    let shared = |input: &Inventory| -> (Vec<usize>, Vec<usize>) {
+      (input.rucksacks.iter().map(|r| r.shared().len()).sorted().collect(),
+       input.rucksacks.chunks(2).map(|g| shared_in_group(g).len()).sorted().collect())
+    };
+    let original = "xyyzzy\nzzz\nxzzxzx\nxxx\nyzxyzx\nyyy";
+    let anonymized = anonymize(original, &mut Rng::new(5));
+    assert_eq!(shared(&generator(original)), shared(&generator(&anonymized.text)));
+    params::uninstall();
+  }
+
+  #[test]
+  fn test_recover_groups() {
+    let sacks = |input: &str| Inventory::parse(input, Alphabet::letters(), 1).unwrap();
+    let badge = |item, priority| Item{item, priority};
+    assert_eq!(Badges::Unique(vec![BadgeGroup{rucksacks: [0, 2, 4], badge: badge('a', 1)},
+                                   BadgeGroup{rucksacks: [1, 3, 5], badge: badge('b', 2)}]),
+               recover_groups(&sacks("ax\nby\naz\nbw\nav\nbu")));
+    assert_eq!("there are several groupings, such as a [1, 2, 3], a [4, 5, 6] and \
+                a [1, 2, 4], a [3, 5, 6]",
+               recover_groups(&sacks("a\na\na\na\na\na")).to_string());
+    assert_eq!("there is no grouping, because 4 rucksacks can't be split into groups of 3",
+               recover_groups(&sacks("a\na\na\na")).to_string());
+    assert_eq!("there is no grouping, because the rucksack on line 1 doesn't share exactly \
+                one item with any two others",
+               recover_groups(&sacks("ab\nab\nab\nab\nab\nab")).to_string());
+    assert_eq!(Badges::Impossible("every way of grouping some of the rucksacks leaves others \
+                                   that can't be grouped".to_string()),
+               recover_groups(&sacks("abc\nad\nbc\nb\nc\nad")));
+  }
+
   #[test]
   fn test_part1() {
     assert_eq!(157, part1(&generator(INPUT)));
@@ -1,52 +1,39 @@
-#[derive(Debug, Eq, PartialEq)]
-pub struct Range {
-  lower: i32,
-  upper: i32,
-}
+use crate::utils::intervals::IntervalSet;
 
-impl Range {
-  fn contains(&self, other: &Self) -> bool {
-    if self.lower == other.lower || self.upper == other.upper {
-      true
-    } else if self.lower < other.lower {
-      self.upper > other.upper
-    } else {
-      self.upper < other.upper
-    }
-  }
+/// The sections that an elf is assigned to clean.
+type Assignment = IntervalSet<i32>;
+type InputType = Vec<(Assignment, Assignment)>;
 
-  fn overlaps(&self, other: &Self) -> bool {
-    let left = i32::max(self.lower, other.lower);
-    let right = i32::min(self.upper, other.upper);
-    left <= right
-  }
+/// Parse an inclusive range like "2-4".
+fn parse_assignment(text: &str) -> Assignment {
+  let (lower, upper) = text.split_once('-')
+    .unwrap_or_else(|| panic!("Can't parse assignment {text}"));
+  let lower: i32 = lower.parse().expect("not integer");
+  let upper: i32 = upper.parse().expect("not integer");
+  IntervalSet::from(lower..upper + 1)
 }
 
-type InputType = Vec<Vec<Range>>;
-
 pub fn generator(input: &str) -> InputType {
   input.lines()
-    .map(|l| l.split(',')
-      .filter_map(|r| r.split_once('-'))
-      .map(|(l,u)|
-        Range{lower: l.parse::<i32>().expect("not integer"),
-              upper: u.parse::<i32>().expect("not integer")})
-      .collect())
+    .map(|l| l.split_once(',').unwrap_or_else(|| panic!("Can't parse pair {l}")))
+    .map(|(left, right)| (parse_assignment(left), parse_assignment(right)))
     .collect()
 }
 
-/// Sum each group and find the maximum
+/// Count the pairs where one assignment covers the other
 pub fn part1(input: &InputType) -> i32 {
-  input.iter().filter(|pair| pair[0].contains(&pair[1])).count() as i32
+  input.iter().filter(|(left, right)| left.is_superset(right) || right.is_superset(left))
+    .count() as i32
 }
 
 pub fn part2(input: &InputType) -> i32 {
-  input.iter().filter(|pair| pair[0].overlaps(&pair[1])).count() as i32
+  input.iter().filter(|(left, right)| !left.intersection(right).is_empty()).count() as i32
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::day4::{Range, generator, part1, part2};
+  use crate::day4::{generator, part1, part2};
+  use crate::utils::intervals::IntervalSet;
 
   const INPUT: &str = "2-4,6-8\n\
                        2-3,4-5\n\
@@ -58,7 +45,7 @@ mod tests {
   #[test]
   fn parsing_test() {
     let result= generator("1-2,3-4");
-    assert_eq!(vec!{vec!{Range{lower: 1, upper: 2}, Range{lower: 3, upper: 4}}}, result);
+    assert_eq!(vec!{(IntervalSet::from(1..3), IntervalSet::from(3..5))}, result);
   }
 
   #[test]
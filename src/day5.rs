@@ -1,4 +1,7 @@
-#[derive(Clone,Debug)]
+use serde::Serialize;
+use crate::utils::simulation::Simulation;
+
+#[derive(Clone,Debug,Serialize)]
 struct State {
   stacks: Vec<Vec<char>>,
 }
@@ -30,27 +33,37 @@ impl State {
     self.stacks.iter().filter_map(|s| s.last()).collect()
   }
 
+  /// Move the crates one at a time.
+  fn do_move(&mut self, cmd: &Move) {
+    for _ in 0..cmd.num_to_move {
+      let ch = self.stacks[cmd.from].pop().unwrap();
+      self.stacks[cmd.to].push(ch);
+    }
+  }
+
+  /// Move the crates all at once, so they keep their order.
+  fn do_move_together(&mut self, cmd: &Move) {
+    let from_posn = self.stacks[cmd.from].len() - cmd.num_to_move;
+    let moving: Vec<char> = self.stacks[cmd.from].drain(from_posn..).collect();
+    self.stacks[cmd.to].extend(moving.iter());
+  }
+
   fn do_moves(&mut self, cmds: &[Move]) -> &mut Self {
     for cmd in cmds {
-      for _ in 0..cmd.num_to_move {
-        let ch = self.stacks[cmd.from].pop().unwrap();
-        self.stacks[cmd.to].push(ch);
-      }
+      self.do_move(cmd);
     }
     self
   }
 
   fn do_moves_together(&mut self, cmds: &[Move]) -> &mut Self {
     for cmd in cmds {
-      let from_posn = self.stacks[cmd.from].len() - cmd.num_to_move;
-      let moving: Vec<char> = self.stacks[cmd.from].drain(from_posn..).collect();
-      self.stacks[cmd.to].extend(moving.iter());
+      self.do_move_together(cmd);
     }
     self
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Move {
   num_to_move: usize,
   from: usize,
@@ -66,7 +79,7 @@ impl Move {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct InputType {
   state: State,
   moves: Vec<Move>,
@@ -80,6 +93,50 @@ pub fn generator(input: &str) -> InputType {
   }
 }
 
+/// The crane doing the moves one at a time.
+pub struct Crane<'a> {
+  state: State,
+  moves: &'a [Move],
+  done: usize,
+  /// Does the crane move several crates at once (part 2)?
+  together: bool,
+}
+
+impl<'a> Crane<'a> {
+  pub fn new(input: &'a InputType, part: usize) -> Self {
+    Crane{state: input.state.clone(), moves: &input.moves, done: 0, together: part == 2}
+  }
+}
+
+impl Simulation for Crane<'_> {
+  fn step(&mut self) {
+    let cmd = &self.moves[self.done];
+    if self.together {
+      self.state.do_move_together(cmd);
+    } else {
+      self.state.do_move(cmd);
+    }
+    self.done += 1;
+  }
+
+  fn is_done(&self) -> bool {
+    self.done == self.moves.len()
+  }
+
+  fn describe(&self) -> String {
+    let mut result = format!("move {}/{}, top: {}\n", self.done, self.moves.len(),
+                             self.state.get_top());
+    for (i, stack) in self.state.stacks.iter().enumerate() {
+      result.push_str(&format!("{}: {}\n", i + 1, stack.iter().collect::<String>()));
+    }
+    if let Some(cmd) = self.moves.get(self.done) {
+      result.push_str(&format!("next: move {} from {} to {}\n", cmd.num_to_move, cmd.from + 1,
+                               cmd.to + 1));
+    }
+    result
+  }
+}
+
 pub fn part1(input: &InputType) -> String {
   input.state.clone().do_moves(&input.moves).get_top()
 }
@@ -90,7 +147,8 @@ pub fn part2(input: &InputType) -> String {
 
 #[cfg(test)]
 mod tests {
-  use crate::day5::{generator, part1, part2};
+  use crate::day5::{generator, part1, part2, Crane};
+  use crate::utils::simulation::Simulation;
 
   const INPUT: &str = "    [D]\n\
                        [N] [C]\n\
@@ -108,6 +166,18 @@ mod tests {
     assert_eq!("NDP", input.state.get_top());
   }
 
+  #[test]
+  fn test_crane() {
+    let input = generator(INPUT);
+    let mut crane = Crane::new(&input, 2);
+    crane.step();
+    assert!(crane.describe().starts_with("move 1/4, top: DCP\n1: ZND\n2: MC\n3: P\n"));
+    while !crane.is_done() {
+      crane.step();
+    }
+    assert!(crane.describe().starts_with("move 4/4, top: MCD\n"));
+  }
+
   #[test]
   fn test_part1() {
     assert_eq!("CMZ", part1(&generator(INPUT)));
@@ -15,7 +15,7 @@ fn is_all_unique(w: &[char]) -> bool {
   true
 }
 
-fn find_unique(input: &Vec<char>, size: usize) -> usize {
+fn find_unique(input: &[char], size: usize) -> usize {
   for posn in 0..(input.len()-size) {
     if is_all_unique(&input[posn..posn+size]) {
       return posn + size
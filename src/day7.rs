@@ -1,9 +1,10 @@
 use std::collections::{HashMap};
+use serde::Serialize;
 
 type InputType = Vec<Command>;
 type OutputType = usize;
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub enum ListObject {
   FILE(String, usize),
   DIR(String)
@@ -26,7 +27,7 @@ impl ListObject {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub enum Command {
   CD(String),
   LS(Vec<ListObject>),
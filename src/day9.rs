@@ -1,19 +1,15 @@
 use std::collections::HashSet;
+use serde::Serialize;
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::grid::Grid;
+use crate::utils::render::{self, Frame};
 
 type InputType = Vec<Command>;
 type OutputType = usize;
 
-#[derive(Clone,Copy,Debug)]
-enum Direction {
-  Up,
-  Right,
-  Down,
-  Left,
-}
-
-#[derive(Debug)]
+#[derive(Debug,Serialize)]
 pub struct Command {
-  dir: Direction,
+  dir: Dir4,
   count: usize,
 }
 
@@ -22,30 +18,22 @@ impl Command {
     let (cmd_str, count_str) = line.split_once(' ').unwrap();
     let count = count_str.parse::<usize>().unwrap();
     let dir = match cmd_str {
-      "R" => Direction::Right,
-      "U" => Direction::Up,
-      "L" => Direction::Left,
-      "D" => Direction::Down,
+      "R" => Dir4::East,
+      "U" => Dir4::North,
+      "L" => Dir4::West,
+      "D" => Dir4::South,
       _ => panic!("Invald command {}", cmd_str),
     };
     Command{dir, count}
   }
 }
 
-#[derive(Clone,Debug,Default,Eq,Hash,PartialEq)]
-struct Position {
-  x: i32,
-  y: i32,
-}
+type Position = Point2<i32>;
 
-impl Position {
-  fn follow(&mut self, leader: &Position) {
-    let (x_delta, y_delta) = (leader.x - self.x, leader.y - self.y);
-    // Does the tail need to move?
-    if !(-1..=1).contains(&x_delta) || !(-1..=1).contains(&y_delta) {
-      self.x += i32::signum(x_delta);
-      self.y += i32::signum(y_delta);
-    }
+fn follow(knot: &mut Position, leader: &Position) {
+  // Does the tail need to move?
+  if knot.chebyshev(leader) > 1 {
+    *knot += (*leader - *knot).signum();
   }
 }
 
@@ -61,25 +49,39 @@ impl Rope {
   }
 
   /// Move the head and tail in a given direction
-  fn go(&mut self, dir: Direction) {
-    match dir {
-      Direction::Up => self.head.y -= 1,
-      Direction::Right => self.head.x += 1,
-      Direction::Down => self.head.y += 1,
-      Direction::Left => self.head.x -= 1,
-    }
+  fn go(&mut self, dir: Dir4) {
+    self.head += dir.delta();
     for t in 0..self.tails.len() {
-      if t == 0 {
-        self.tails[t].follow(&self.head);
-      } else {
-        let prev= self.tails[t-1].clone();
-        self.tails[t].follow(&prev);
-      }
+      let leader = if t == 0 { self.head } else { self.tails[t-1] };
+      follow(&mut self.tails[t], &leader);
     }
   }
 
   fn get_tail(&self) -> Position {
-    self.tails.last().unwrap().clone()
+    *self.tails.last().unwrap()
+  }
+
+  /// Draw the spots the tail has visited, the start, and the knots, which
+  /// are 'H' for the head and numbered after that.
+  fn draw(&self, visited: &HashSet<Position>) -> Frame {
+    let knots: Vec<Position> = std::iter::once(self.head).chain(self.tails.iter().copied())
+      .collect();
+    let start = [Position::default()];
+    let all = || visited.iter().chain(knots.iter()).chain(start.iter());
+    let left = all().map(|p| p.x).min().unwrap();
+    let top = all().map(|p| p.y).min().unwrap();
+    let width = (all().map(|p| p.x).max().unwrap() - left + 1) as usize;
+    let height = (all().map(|p| p.y).max().unwrap() - top + 1) as usize;
+    let mut frame = Grid::new(width, height, '.');
+    let mut put = |p: Position, ch: char| frame[((p.x - left) as usize, (p.y - top) as usize)] = ch;
+    for &spot in visited {
+      put(spot, '#');
+    }
+    put(start[0], 's');
+    for (i, &knot) in knots.iter().enumerate().rev() {
+      put(knot, if i == 0 { 'H' } else { char::from_digit((i % 10) as u32, 10).unwrap() });
+    }
+    frame
   }
 }
 
@@ -96,6 +98,7 @@ fn run_commands(input: &InputType, num_tails: usize) -> OutputType {
     for _ in 0..cmd.count {
       rope.go(cmd.dir);
       spots.insert(rope.get_tail());
+      render::frame(|| rope.draw(&spots));
     }
   }
   spots.len()
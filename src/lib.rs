@@ -1,3 +1,24 @@
 pub mod utils;
 
-day_list!(1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25);
\ No newline at end of file
+use utils::anonymize::Anonymizer;
+use utils::simulation::Builder;
+
+day_list!(1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25);
+
+/// The days whose inputs can be anonymized.
+pub const ANONYMIZERS: &[(&str, Anonymizer)] = &[
+  ("day3", day3::anonymize),
+  ("day15", day15::anonymize),
+  ("day16", day16::anonymize),
+  ("day18", day18::anonymize),
+  ("day21", day21::anonymize),
+];
+
+/// The days that can be stepped through with the debugger.
+pub const SIMULATIONS: &[(&str, Builder)] = &[
+  ("day5", &|data, part, run| run(&mut day5::Crane::new(&day5::generator(data), part))),
+  ("day10", &|data, _, run| run(&mut day10::Cpu::new(&day10::generator(data)))),
+  ("day17", &|data, part, run| run(&mut day17::RockDrops::new(&day17::generator(data), part))),
+  ("day23", &|data, part, run| run(&mut day23::Rounds::new(&day23::generator(data), part))),
+  ("day24", &|data, part, run| run(&mut day24::Expedition::new(&day24::generator(data), part))),
+];
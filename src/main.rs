@@ -1,96 +1,260 @@
-use omalley_aoc2022::{FUNCS,NAMES,utils};
+use omalley_aoc2022::{ANONYMIZERS,DUMPS,FUNCS,NAMES,SIMULATIONS,utils};
+use omalley_aoc2022::utils::answers::Answers;
+use omalley_aoc2022::utils::config::{self, Config, Days, Dump};
 
 use argh::FromArgs;
 use colored::Colorize;
-use serde::{Deserialize,Serialize};
-use std::collections::BTreeMap;
-use std::fs::File;
+use std::fs;
+use std::io::{self, IsTerminal};
 use std::path::Path;
 
+/// The size in pixels of each cell in the rendered images.
+const PPM_SCALE: usize = 4;
+
 #[derive(FromArgs)]
 /** Solution for Advent of Code (https://adventofcode.com/)*/
 struct Args {
-  /// the input directory
-  #[argh(option, short='i', default="String::from(\"input\")")]
-  input: String,
+  /// the input directory (defaults to input)
+  #[argh(option, short='i')]
+  input: Option<String>,
+
+  /// write an HTML report of the run to the given file, or none to not
+  /// write one
+  #[argh(option)]
+  html: Option<String>,
+
+  /// write a JUnit XML report of the run to the given file, or none to not
+  /// write one
+  #[argh(option)]
+  junit: Option<String>,
+
+  /// print the parsed input for the selected day as yaml or json instead of
+  /// solving it, or none to solve it
+  #[argh(option)]
+  dump_input: Option<Dump>,
+
+  /// write frames of the simulations as PPM images to the given directory,
+  /// draw them on the terminal if it is "-", or none to not render them
+  #[argh(option)]
+  render: Option<String>,
+
+  /// render one of every N frames (defaults to 1)
+  #[argh(option)]
+  render_every: Option<usize>,
+
+  /// write anonymized copies of the inputs and their answers to the given
+  /// directory instead of solving them
+  #[argh(option)]
+  anonymize: Option<String>,
+
+  /// the random seed for anonymizing (defaults to 1)
+  #[argh(option)]
+  seed: Option<u64>,
+
+  /// set a solver parameter as name=value, such as day1.top=5
+  #[argh(option)]
+  param: Vec<String>,
+
+  /// print the settings from the command line, the AOC_* environment
+  /// variables, and the aoc2022.yml config file, and then exit
+  #[argh(switch)]
+  print_config: bool,
 
-  /// a single day to execute (defaults to all)
+  #[argh(subcommand)]
+  command: Option<Command>,
+
+  /// a single day to execute, or all (the default)
   #[argh(positional)]
-  day: Option<usize>,
+  day: Option<Days>,
 }
 
-#[derive(Default,Deserialize,Serialize)]
-struct Answers {
-  // map from day name to answers
-  days: BTreeMap<String,Vec<String>>,
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+  Debug(DebugArgs),
 }
 
-impl Answers {
-  fn make_filename(directory: &str) -> String {
-    Path::new(directory).join("answers.yml").to_string_lossy().to_string()
-  }
+#[derive(FromArgs)]
+/// step through a day's simulation with commands read from stdin
+#[argh(subcommand, name="debug")]
+struct DebugArgs {
+  /// the day to debug
+  #[argh(positional)]
+  day: usize,
 
-  fn read(directory: &str) -> Self {
-    if let Ok(f) = File::open(Self::make_filename(directory)) {
-      serde_yaml::from_reader(f).expect("Could not read answers")
-    } else {
-      Self::default()
-    }
-  }
-
-  fn update(&mut self, delta_list: &Vec<utils::DayResult>) {
-    for delta in delta_list {
-      let new_val = delta.get_answers();
-      if let Some(prev) =
-          self.days.insert(delta.day.to_string(), new_val.clone()) {
-        if prev != new_val {
-          println!("{}", format!("Output for {} changed from {:?} to {:?}!",
-                                 delta.pretty_day(), prev, new_val).bold());
+  /// which part's simulation to run (defaults to 1)
+  #[argh(option, default="1")]
+  part: usize,
+}
+
+/// Rewrite the inputs of the days that support it into the output directory,
+/// run the solvers on the new inputs, and check the answers against the ones
+/// derived from the original answers. Returns whether they all matched.
+fn anonymize(in_dir: &str, out_dir: &str, seed: u64, inputs: &[String],
+             day_filter: Option<usize>) -> bool {
+    fs::create_dir_all(out_dir).expect("Can't create anonymize dir");
+    let old_answers = Answers::read(in_dir);
+    let mut rng = utils::anonymize::Rng::new(seed);
+    let mut results = Vec::new();
+    let mut all_match = true;
+    for (name, anonymizer) in ANONYMIZERS {
+        let day = NAMES.iter().position(|x| x == name).unwrap();
+        if day_filter.is_some_and(|d| d != day) {
+            continue;
         }
-      }
+        let anonymized = anonymizer(&inputs[day], &mut rng);
+        fs::write(Path::new(out_dir).join(format!("{name}.txt")), &anonymized.text)
+          .expect("Can't write anonymized input");
+        let result = FUNCS[day](&anonymized.text);
+        let answers = result.get_answers();
+        match old_answers.get(name).and_then(|old| anonymized.answers.apply(old)) {
+            Some(expected) if expected == answers =>
+              println!("{name}: {}", "answers verified".green()),
+            Some(expected) => {
+                all_match = false;
+                println!("{name}: {}", format!("expected {expected:?}, but got {answers:?}")
+                  .red().bold());
+            },
+            None => println!("{name}: {}", "answers recomputed".dimmed()),
+        }
+        results.push(result);
+    }
+    let mut new_answers = Answers::read(out_dir);
+    new_answers.update(&results);
+    new_answers.write(out_dir);
+    all_match
+}
+
+impl Args {
+    /// The settings that were given on the command line.
+    fn config(&self) -> Result<Config, String> {
+        let params = self.param.iter().map(|p| utils::params::parse_assignment(p))
+          .collect::<Result<_, _>>()?;
+        Ok(Config{input: self.input.clone(), day: self.day, html: self.html.clone(),
+                  junit: self.junit.clone(), dump_input: self.dump_input,
+                  render: self.render.clone(), render_every: self.render_every, seed: self.seed,
+                  params})
     }
-  }
-
-  fn write(&self, directory: &str) {
-    let f = std::fs::OpenOptions::new()
-      .write(true)
-      .create(true)
-      .truncate(true)
-      .open(Self::make_filename(directory))
-      .expect("Couldn't open file");
-    serde_yaml::to_writer(f, self).unwrap();
-  }
 }
 
 fn main() {
     let args: Args = argh::from_env();
+    let (config, config_file) = args.config().and_then(Config::load).unwrap_or_else(|err| {
+        eprintln!("{}", err.red().bold());
+        std::process::exit(2);
+    });
+    if args.print_config {
+        match &config_file {
+            Some(path) => println!("# settings from {}, the environment, and the command line",
+                                   path.display()),
+            None => println!("# no {} found, so settings from the environment and the \
+                              command line", config::FILE_NAME),
+        }
+        print!("{}", config.to_yaml());
+        return;
+    }
+    let input_dir = config.input.clone().unwrap();
+    utils::params::install(config.params.clone());
     // Did the user pick a single day to run
-    let day_filter: Option<usize> = match args.day {
-        Some(day) => {
+    let day_filter: Option<usize> = match config.day {
+        Some(Days::Only(day)) => {
             let name = format!("day{}", day);
             Some(NAMES.iter().position(|x| **x == name)
               .expect("Requested an unimplemented day"))
         },
-        None => None
+        Some(Days::All) | None => None
     };
     // Read the inputs from the given directory
-    println!("{} {}\n", "Reading from".bold(), &args.input);
-    let inputs = utils::read_inputs(&args.input, NAMES)
-      .expect("Can't read input dir");
+    let inputs: Vec<String> = utils::read_inputs(&input_dir, NAMES)
+      .expect("Can't read input dir")
+      .iter().zip(NAMES)
+      .map(|(raw, name)| {
+        let normalized = utils::normalize::normalize(raw);
+        for change in normalized.changes.iter().filter(|c| c.is_notable()) {
+          eprintln!("{}", format!("{name}: {change}").dimmed());
+        }
+        normalized.text
+      })
+      .collect();
+
+    if let Some(Command::Debug(debug)) = &args.command {
+        let name = format!("day{}", debug.day);
+        let Some((_, build)) = SIMULATIONS.iter().find(|(day, _)| *day == name) else {
+            let days = SIMULATIONS.iter().map(|(day, _)| *day).collect::<Vec<_>>();
+            eprintln!("{}", format!("{name} doesn't have a simulation; try one of {}",
+                                    days.join(", ")).red().bold());
+            std::process::exit(2);
+        };
+        if !(1..=2).contains(&debug.part) {
+            eprintln!("{}", format!("There is no part {} to debug; use 1 or 2", debug.part)
+              .red().bold());
+            std::process::exit(2);
+        }
+        let day = NAMES.iter().position(|x| *x == name).unwrap();
+        build(&inputs[day], debug.part, &mut |sim| {
+            utils::simulation::debug(sim, io::stdin().lock(), io::stdout().lock())
+              .expect("Can't run the debugger")
+        });
+        return;
+    }
+
+    if let Some(out_dir) = &args.anonymize {
+        if !anonymize(&input_dir, out_dir, config.seed.unwrap(), &inputs, day_filter) {
+            std::process::exit(1);
+        }
+        return;
+    }
 
+    if let Some(Dump::Format(format)) = config.dump_input {
+      let day = day_filter.unwrap_or_else(|| {
+        eprintln!("{}", "Dumping the input requires a day".red().bold());
+        std::process::exit(2);
+      });
+      println!("{}", DUMPS[day](&inputs[day], format));
+      return;
+    }
+    println!("{} {}\n", "Reading from".bold(), &input_dir);
+
+    // Show a status line for long-running days if a person is watching
+    if io::stdout().is_terminal() && io::stderr().is_terminal() {
+      utils::progress::install(Box::new(utils::progress::TerminalSink));
+    }
+    let render_every = config.render_every.unwrap();
+    match config.render.as_deref() {
+        Some("-") => utils::render::install(Box::new(utils::render::TerminalSink), render_every),
+        Some(dir) => utils::render::install(
+            Box::new(utils::render::PpmSink::new(dir, PPM_SCALE).expect("Can't create render dir")),
+            render_every),
+        None => {},
+    }
     let (elapsed, results) = utils::time(&|| {
         FUNCS.iter().enumerate()
           .filter(|(p, _)| day_filter.is_none() || day_filter.unwrap() == *p)
           .map(|(p, f)| f(&inputs[p]))
           .collect::<Vec<utils::DayResult>>()
     });
+    utils::progress::uninstall();
+    utils::render::uninstall();
 
     for r in &results {
       println!("{}", r);
     }
     println!("{} {}", "Overall runtime".bold(), format!("({:.2?})", elapsed).dimmed());
 
-    let mut old_answers = Answers::read(&args.input);
-    old_answers.update(&results);
-    old_answers.write(&args.input);
+    let mut old_answers = Answers::read(&input_dir);
+    if let Some(filename) = &config.html {
+      utils::html::write_report(filename, &input_dir, &results, &old_answers, elapsed)
+        .expect("Can't write HTML report");
+    }
+    if let Some(filename) = &config.junit {
+      utils::junit::write_report(filename, &input_dir, &results, &old_answers, elapsed)
+        .expect("Can't write JUnit report");
+    }
+    // Parameters change the answers, so only the defaults are recorded
+    if config.params.is_empty() {
+      old_answers.update(&results);
+      old_answers.write(&input_dir);
+    } else {
+      println!("{}", "Not updating answers.yml because parameters are set".dimmed());
+    }
 }
@@ -1,14 +1,57 @@
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use std::cmp::min;
 use std::{fmt, fs, io};
 use std::path::Path;
+use std::str::FromStr;
 use std::time;
 
+pub mod answer;
+pub mod anonymize;
+pub mod answers;
+pub mod automaton;
+pub mod bitset;
+pub mod checked;
+pub mod config;
+pub mod cycle;
+pub mod geom;
+pub mod grid;
+pub mod html;
+pub mod intervals;
+pub mod junit;
+pub mod normalize;
+pub mod params;
+pub mod parse;
+pub mod progress;
+pub mod render;
+pub mod search;
+pub mod simulation;
+
+pub use answer::Answer;
+
+/// Escape the characters that are special in XML and HTML attributes and
+/// text.
+pub fn escape_xml(s: &str) -> String {
+  let mut result = String::with_capacity(s.len());
+  for ch in s.chars() {
+    match ch {
+      '&' => result.push_str("&amp;"),
+      '<' => result.push_str("&lt;"),
+      '>' => result.push_str("&gt;"),
+      '"' => result.push_str("&quot;"),
+      '\'' => result.push_str("&apos;"),
+      '\n' => result.push_str("&#10;"),
+      _ => result.push(ch),
+    }
+  }
+  result
+}
+
 /// Format the output of each line of the output.
 /// Includes the category, time, and result.
 fn pretty_print(f: &mut fmt::Formatter<'_>, line: &str,
                 duration: time::Duration,
-                output: Option<&str>) -> fmt::Result {
+                output: Option<&Answer>) -> fmt::Result {
     const DISPLAY_WIDTH: usize = 40;
 
     let duration = format!("({:.2?})", duration);
@@ -20,15 +63,18 @@ fn pretty_print(f: &mut fmt::Formatter<'_>, line: &str,
             let dots = DISPLAY_WIDTH - min(DISPLAY_WIDTH - 5, width) - 2;
             write!(f, " {}", ".".repeat(dots).dimmed())?;
 
-            if output.contains('\n') {
-                writeln!(f)?;
-
-                for line in output.trim_matches('\n').lines() {
-                    writeln!(f, "    {}", line.bold())?;
-                }
-                Ok(())
-            } else {
-                writeln!(f, " {}", output.bold())
+            match output {
+                Answer::Grid(rows) => {
+                    writeln!(f)?;
+
+                    for line in rows {
+                        writeln!(f, "    {}", line.bold())?;
+                    }
+                    Ok(())
+                },
+                Answer::None => writeln!(f, " {}", "none".dimmed()),
+                Answer::Error(message) => writeln!(f, " {}", message.red().bold()),
+                _ => writeln!(f, " {}", output.to_string().bold()),
             }
         },
         None => writeln!(f),
@@ -54,12 +100,40 @@ pub fn read_inputs(in_dir: &str, days: &[&str]) -> io::Result<Vec<String>> {
   data.into_iter().collect()
 }
 
+/// The formats that a day's parsed input can be dumped in.
+#[derive(Clone,Copy,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DumpFormat {
+  Yaml,
+  Json,
+}
+
+impl FromStr for DumpFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "yaml" => Ok(DumpFormat::Yaml),
+      "json" => Ok(DumpFormat::Json),
+      _ => Err(format!("unknown format '{s}', expected yaml or json")),
+    }
+  }
+}
+
+/// Convert a day's parsed input into text in the given format.
+pub fn dump<T: Serialize>(input: &T, format: DumpFormat) -> String {
+  match format {
+    DumpFormat::Yaml => serde_yaml::to_string(input).expect("Can't convert to YAML"),
+    DumpFormat::Json => serde_json::to_string_pretty(input).expect("Can't convert to JSON"),
+  }
+}
+
 /// The times and results of running a day's code.
 pub struct DayResult {
     pub day: String,
     pub generate_time: time::Duration,
-    pub part1: (time::Duration, String),
-    pub part2: (time::Duration, String),
+    pub part1: (time::Duration, Answer),
+    pub part2: (time::Duration, Answer),
 }
 
 impl DayResult {
@@ -69,8 +143,8 @@ impl DayResult {
   }
 
   /// Get the answers without the times
-  pub fn get_answers(&self) -> Vec<String> {
-    vec![self.part1.1.to_string(), self.part2.1.to_string()]
+  pub fn get_answers(&self) -> Vec<Answer> {
+    vec![self.part1.1.clone(), self.part2.1.clone()]
   }
 }
 
@@ -93,13 +167,30 @@ macro_rules! day_list_internal {
         /// Build a lambda to run each day's code
         pub const FUNCS : &[&dyn Fn(&str) -> $crate::utils::DayResult] = &[
             $(&|data| {
+                use $crate::utils::{checked, progress, render};
+                progress::start(concat!(stringify!($day), " generator"));
+                checked::start(concat!(stringify!($day), " generator"));
                 let (generate_time, input) = $crate::utils::time(&|| $day::generator(data));
+                let generator_overflow = checked::take();
+                progress::start(concat!(stringify!($day), " part 1"));
+                render::start(concat!(stringify!($day), " part 1"));
+                checked::start(concat!(stringify!($day), " part 1"));
                 let part1 = $crate::utils::time(&|| $day::part1(&input));
+                let part1 = (part1.0, checked::check(generator_overflow.clone(), part1.1.into()));
+                progress::finish();
+                progress::start(concat!(stringify!($day), " part 2"));
+                render::start(concat!(stringify!($day), " part 2"));
+                checked::start(concat!(stringify!($day), " part 2"));
                 let part2 = $crate::utils::time(&|| $day::part2(&input));
+                let part2 = (part2.0, checked::check(generator_overflow, part2.1.into()));
+                progress::finish();
                 $crate::utils::DayResult{day: stringify!($day).to_string(),
-                          generate_time,
-                          part1: (part1.0, part1.1.to_string()),
-                          part2: (part2.0, part2.1.to_string())}},)*
+                          generate_time, part1, part2}},)*
+        ];
+
+        /// Build a lambda to parse each day's input and dump the result.
+        pub const DUMPS : &[&dyn Fn(&str, $crate::utils::DumpFormat) -> String] = &[
+            $(&|data, format| $crate::utils::dump(&$day::generator(data), format),)*
         ];
 
         /// Define the list of implemented day names.
@@ -0,0 +1,185 @@
+//! Rewriting puzzle inputs into different, but equivalent, ones.
+//!
+//! The real inputs can't be published, but the hard cases in them are worth
+//! sharing. Each day that supports it has an `anonymize` function that
+//! renames, reorders, or moves things in its input and says how the answers
+//! change. The same seed always produces the same output.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+use crate::utils::Answer;
+
+/// A xorshift64* random number generator, which is plenty for shuffling
+/// inputs.
+#[derive(Clone,Debug)]
+pub struct Rng {
+  state: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Self {
+    // the state must never be zero
+    Rng{state: (seed ^ 0x9e37_79b9_7f4a_7c15) | 1}
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state ^= self.state >> 12;
+    self.state ^= self.state << 25;
+    self.state ^= self.state >> 27;
+    self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+  }
+
+  /// Pick a number in 0..bound.
+  pub fn below(&mut self, bound: usize) -> usize {
+    (self.next_u64() % bound as u64) as usize
+  }
+
+  /// Pick a number in the given range.
+  pub fn between(&mut self, range: RangeInclusive<i64>) -> i64 {
+    range.start() + (self.next_u64() % (range.end() - range.start() + 1) as u64) as i64
+  }
+
+  pub fn coin(&mut self) -> bool {
+    self.next_u64() & 1 == 1
+  }
+
+  /// Put the items in a random order.
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.below(i + 1));
+    }
+  }
+}
+
+/// Compute the new answer to a part (0 or 1) from the original answer.
+pub type MapFn = Box<dyn Fn(usize, &Answer) -> Answer>;
+
+/// How the answers to the rewritten input follow from the original ones.
+pub enum AnswerMap {
+  /// The answers don't change.
+  Same,
+  /// Each part's answer is computed from the original one.
+  Map(MapFn),
+  /// The answers can't be derived, so they have to be recomputed by running
+  /// the solver on the new input.
+  Recompute,
+}
+
+impl AnswerMap {
+  /// The answers for the new input, if they can be derived from the old ones.
+  pub fn apply(&self, old: &[Answer]) -> Option<Vec<Answer>> {
+    match self {
+      AnswerMap::Same => Some(old.to_vec()),
+      AnswerMap::Map(map) => Some(old.iter().enumerate().map(|(part, a)| map(part, a)).collect()),
+      AnswerMap::Recompute => None,
+    }
+  }
+}
+
+/// A rewritten input and how its answers relate to the original ones.
+pub struct Anonymized {
+  pub text: String,
+  pub answers: AnswerMap,
+}
+
+/// Rewrite a day's input using the random numbers.
+pub type Anonymizer = fn(&str, &mut Rng) -> Anonymized;
+
+/// Pick a new name for each of the names, made of the same number of
+/// letters from the range. The names in `keep` are left alone, and no other
+/// name is renamed to one of them.
+pub fn rename<'a>(names: impl IntoIterator<Item=&'a str>, keep: &[&str],
+                  letters: RangeInclusive<char>, rng: &mut Rng) -> HashMap<String, String> {
+  let letters: Vec<char> = letters.collect();
+  let mut used: HashSet<String> = keep.iter().map(|k| k.to_string()).collect();
+  let mut result = HashMap::new();
+  for name in names {
+    if keep.contains(&name) || result.contains_key(name) {
+      continue
+    }
+    let new_name = loop {
+      let candidate: String = (0..name.chars().count())
+        .map(|_| letters[rng.below(letters.len())]).collect();
+      if used.insert(candidate.clone()) {
+        break candidate
+      }
+    };
+    result.insert(name.to_string(), new_name);
+  }
+  result
+}
+
+/// Replace each word of the text that is in the map with its new name.
+pub fn replace_words(text: &str, names: &HashMap<String, String>) -> String {
+  let mut result = String::with_capacity(text.len());
+  let mut word = String::new();
+  for ch in text.chars().chain(std::iter::once('\n')) {
+    if ch.is_alphanumeric() {
+      word.push(ch);
+    } else {
+      result.push_str(names.get(&word).unwrap_or(&word));
+      word.clear();
+      result.push(ch);
+    }
+  }
+  // drop the extra newline
+  result.pop();
+  result
+}
+
+/// Put the lines of the text in a random order.
+pub fn shuffle_lines(text: &str, rng: &mut Rng) -> String {
+  let mut lines: Vec<&str> = text.lines().collect();
+  rng.shuffle(&mut lines);
+  lines.iter().map(|l| format!("{l}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::Answer;
+  use crate::utils::anonymize::{rename, replace_words, shuffle_lines, AnswerMap, Rng};
+
+  #[test]
+  fn test_rng() {
+    let mut rng = Rng::new(7);
+    let first: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+    assert_eq!(first, (0..4).map({ let mut r = Rng::new(7); move |_| r.next_u64() })
+      .collect::<Vec<_>>());
+    assert_ne!(first[0], Rng::new(8).next_u64());
+    let mut items: Vec<usize> = (0..20).collect();
+    rng.shuffle(&mut items);
+    assert_ne!((0..20).collect::<Vec<_>>(), items);
+    items.sort();
+    assert_eq!((0..20).collect::<Vec<_>>(), items);
+    assert!((0..100).all(|_| (-3..=3).contains(&rng.between(-3..=3))));
+  }
+
+  #[test]
+  fn test_rename() {
+    let mut rng = Rng::new(1);
+    let names = rename(["AA", "BB", "CC", "BB"], &["AA"], 'A'..='Z', &mut rng);
+    assert_eq!(2, names.len());
+    assert!(names.values().all(|n| n.len() == 2 && n != "AA"));
+    assert_ne!(names["BB"], names["CC"]);
+    let text = replace_words("Valve AA leads to BB, CC\nValve BB", &names);
+    assert_eq!(format!("Valve AA leads to {bb}, {cc}\nValve {bb}", bb = names["BB"],
+                       cc = names["CC"]), text);
+    let shuffled = shuffle_lines("a\nb\nc\n", &mut rng);
+    let mut lines: Vec<&str> = shuffled.lines().collect();
+    lines.sort();
+    assert_eq!(vec!["a", "b", "c"], lines);
+  }
+
+  #[test]
+  fn test_answer_map() {
+    let old = [Answer::Integer(3), Answer::Integer(4)];
+    assert_eq!(Some(old.to_vec()), AnswerMap::Same.apply(&old));
+    assert_eq!(None, AnswerMap::Recompute.apply(&old));
+    let double = AnswerMap::Map(Box::new(|part, answer| match (part, answer) {
+      (1, Answer::Integer(n)) => Answer::Integer(n * 2),
+      _ => answer.clone(),
+    }));
+    assert_eq!(Some(vec![Answer::Integer(3), Answer::Integer(8)]), double.apply(&old));
+  }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, SeqAccess, Visitor};
+use std::fmt;
+
+/// The answer to one part of a day's puzzle.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Answer {
+  Integer(i128),
+  Text(String),
+  /// A picture, such as a CRT display, stored as a list of rows.
+  Grid(Vec<String>),
+  /// The part doesn't have an answer.
+  None,
+  /// The solver failed, such as by overflowing, so the answer can't be
+  /// trusted.
+  Error(String),
+}
+
+impl Answer {
+  /// Build a grid from a multi-line string.
+  pub fn grid(picture: &str) -> Self {
+    Answer::Grid(picture.trim_matches('\n').lines().map(|l| l.to_string()).collect())
+  }
+
+  /// Older answer files stored every answer as a string, so recognize the
+  /// integers, pictures, and empty answers in them.
+  pub fn from_legacy(self) -> Self {
+    match self {
+      Answer::Text(v) if v.is_empty() => Answer::None,
+      Answer::Text(v) if v.contains('\n') => Answer::grid(&v),
+      Answer::Text(v) => match v.parse::<i128>() {
+        Ok(n) => Answer::Integer(n),
+        Err(_) => Answer::Text(v),
+      },
+      other => other,
+    }
+  }
+}
+
+macro_rules! answer_from_integer {
+  ( $($t:ty),* ) => {
+    $(impl From<$t> for Answer {
+      fn from(val: $t) -> Self {
+        Answer::Integer(val as i128)
+      }
+    })*
+  }
+}
+
+answer_from_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl From<String> for Answer {
+  fn from(val: String) -> Self {
+    Answer::Text(val)
+  }
+}
+
+impl From<&str> for Answer {
+  fn from(val: &str) -> Self {
+    Answer::Text(val.to_string())
+  }
+}
+
+impl From<()> for Answer {
+  fn from(_: ()) -> Self {
+    Answer::None
+  }
+}
+
+/// Integers and text are displayed directly, grids as one row per a line,
+/// and a missing answer as an empty string.
+impl fmt::Display for Answer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Answer::Integer(n) => write!(f, "{n}"),
+      Answer::Text(s) => write!(f, "{s}"),
+      Answer::Grid(rows) => {
+        for row in rows {
+          writeln!(f, "{row}")?;
+        }
+        Ok(())
+      },
+      Answer::None => Ok(()),
+      Answer::Error(message) => write!(f, "{message}"),
+    }
+  }
+}
+
+/// Integers are written as YAML integers, text as strings, grids as a list of
+/// rows, and a missing answer as null. Errors aren't answers, so they are
+/// also written as null.
+impl Serialize for Answer {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Answer::Integer(n) => serializer.serialize_i128(*n),
+      Answer::Text(s) => serializer.serialize_str(s),
+      Answer::Grid(rows) => rows.serialize(serializer),
+      Answer::None | Answer::Error(_) => serializer.serialize_unit(),
+    }
+  }
+}
+
+struct AnswerVisitor;
+
+impl<'de> Visitor<'de> for AnswerVisitor {
+  type Value = Answer;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "an integer, string, list of rows, or null")
+  }
+
+  fn visit_i64<E: de::Error>(self, v: i64) -> Result<Answer, E> {
+    Ok(Answer::Integer(v as i128))
+  }
+
+  fn visit_u64<E: de::Error>(self, v: u64) -> Result<Answer, E> {
+    Ok(Answer::Integer(v as i128))
+  }
+
+  fn visit_i128<E: de::Error>(self, v: i128) -> Result<Answer, E> {
+    Ok(Answer::Integer(v))
+  }
+
+  fn visit_str<E: de::Error>(self, v: &str) -> Result<Answer, E> {
+    Ok(Answer::Text(v.to_string()))
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Answer, A::Error> {
+    let mut rows = Vec::new();
+    while let Some(row) = seq.next_element::<String>()? {
+      rows.push(row);
+    }
+    Ok(Answer::Grid(rows))
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<Answer, E> {
+    Ok(Answer::None)
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<Answer, E> {
+    Ok(Answer::None)
+  }
+}
+
+impl<'de> Deserialize<'de> for Answer {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(AnswerVisitor)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::Answer;
+
+  #[test]
+  fn test_yaml() {
+    let answers = vec![Answer::Integer(19573408701), Answer::Text("CMZ".to_string()),
+                       Answer::grid("#.#\n.#.\n"), Answer::None];
+    let yaml = serde_yaml::to_string(&answers).unwrap();
+    assert_eq!("- 19573408701\n- CMZ\n- - '#.#'\n  - .#.\n- null\n", yaml);
+    assert_eq!(answers, serde_yaml::from_str::<Vec<Answer>>(&yaml).unwrap());
+  }
+
+  #[test]
+  fn test_text_round_trip() {
+    let answers = vec![Answer::Text("122".to_string()), Answer::Text(String::new())];
+    let yaml = serde_yaml::to_string(&answers).unwrap();
+    assert_eq!("- '122'\n- ''\n", yaml);
+    assert_eq!(answers, serde_yaml::from_str::<Vec<Answer>>(&yaml).unwrap());
+  }
+
+  #[test]
+  fn test_legacy_yaml() {
+    let answers: Vec<Answer> =
+      serde_yaml::from_str("- '1042'\n- CMZ\n- \"#.#\\n.#.\\n\"\n- ''\n").unwrap();
+    assert_eq!(vec![Answer::Integer(1042), Answer::Text("CMZ".to_string()),
+                    Answer::grid("#.#\n.#."), Answer::None],
+               answers.into_iter().map(Answer::from_legacy).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_display() {
+    assert_eq!("-12", Answer::from(-12i64).to_string());
+    assert_eq!("#.#\n.#.\n", Answer::grid("#.#\n.#.").to_string());
+    assert_eq!("", Answer::from(()).to_string());
+  }
+}
@@ -0,0 +1,163 @@
+use colored::Colorize;
+use serde::{Deserialize,Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use crate::utils::{Answer, DayResult};
+
+/// How a computed answer compares to the stored one.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Check {
+  /// The answer matches the stored answer.
+  Match,
+  /// The answer differs from the stored answer, which is included.
+  Mismatch(Answer),
+  /// There is no stored answer to compare with.
+  Missing,
+  /// The part has no answer, so there is nothing to check.
+  NoAnswer,
+  /// The solver failed, so there is no answer to check.
+  Error,
+}
+
+/// The version of answers.yml that stores each answer with its own type.
+/// Files without a version stored every answer as a string.
+const VERSION: u32 = 2;
+
+/// The answers for each day that are stored in an input directory's answers.yml.
+#[derive(Deserialize,Serialize)]
+#[serde(from = "StoredAnswers")]
+pub struct Answers {
+  version: u32,
+  // map from day name to answers
+  days: BTreeMap<String,Vec<Answer>>,
+}
+
+/// The contents of answers.yml before any old answers are converted.
+#[derive(Deserialize)]
+struct StoredAnswers {
+  #[serde(default)]
+  version: u32,
+  days: BTreeMap<String,Vec<Answer>>,
+}
+
+impl From<StoredAnswers> for Answers {
+  fn from(stored: StoredAnswers) -> Self {
+    let mut days = stored.days;
+    if stored.version < VERSION {
+      for answers in days.values_mut() {
+        *answers = answers.drain(..).map(Answer::from_legacy).collect();
+      }
+    }
+    Answers{version: VERSION, days}
+  }
+}
+
+impl Default for Answers {
+  fn default() -> Self {
+    Answers{version: VERSION, days: BTreeMap::new()}
+  }
+}
+
+impl Answers {
+  fn make_filename(directory: &str) -> String {
+    Path::new(directory).join("answers.yml").to_string_lossy().to_string()
+  }
+
+  /// Read the answers from the given directory or return an empty set if
+  /// there aren't any.
+  pub fn read(directory: &str) -> Self {
+    if let Ok(f) = File::open(Self::make_filename(directory)) {
+      serde_yaml::from_reader(f).expect("Could not read answers")
+    } else {
+      Self::default()
+    }
+  }
+
+  /// Get the stored answers for the given day.
+  pub fn get(&self, day: &str) -> Option<&[Answer]> {
+    self.days.get(day).map(|v| v.as_slice())
+  }
+
+  /// Describe a list of answers for a message.
+  fn describe(answers: &[Answer]) -> String {
+    format!("{:?}", answers.iter().map(|a| a.to_string()).collect::<Vec<String>>())
+  }
+
+  /// Compare the answer to the given part (0 or 1) with the stored one.
+  pub fn check(&self, day: &str, part: usize, answer: &Answer) -> Check {
+    match answer {
+      Answer::None => return Check::NoAnswer,
+      Answer::Error(_) => return Check::Error,
+      _ => {},
+    }
+    match self.days.get(day).and_then(|v| v.get(part)) {
+      Some(expected) if expected == answer => Check::Match,
+      Some(expected) => Check::Mismatch(expected.clone()),
+      None => Check::Missing,
+    }
+  }
+
+  /// Replace the answers with the new results and report any changes. The
+  /// stored answers for parts that failed are kept.
+  pub fn update(&mut self, delta_list: &[DayResult]) {
+    for delta in delta_list {
+      let old_val = self.days.get(&delta.day);
+      let new_val: Vec<Answer> = delta.get_answers().into_iter().enumerate()
+        .map(|(part, answer)| match answer {
+          Answer::Error(_) => old_val.and_then(|v| v.get(part)).cloned().unwrap_or(Answer::None),
+          _ => answer,
+        })
+        .collect();
+      if let Some(prev) =
+          self.days.insert(delta.day.to_string(), new_val.clone()) {
+        if prev != new_val {
+          println!("{}", format!("Output for {} changed from {} to {}!",
+                                 delta.pretty_day(), Self::describe(&prev),
+                                 Self::describe(&new_val)).bold());
+        }
+      }
+    }
+  }
+
+  pub fn write(&self, directory: &str) {
+    let f = std::fs::OpenOptions::new()
+      .write(true)
+      .create(true)
+      .truncate(true)
+      .open(Self::make_filename(directory))
+      .expect("Couldn't open file");
+    serde_yaml::to_writer(f, self).unwrap();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::Answer;
+  use crate::utils::answers::{Answers, Check};
+
+  #[test]
+  fn test_check() {
+    let answers: Answers =
+      serde_yaml::from_str("days:\n  day1:\n  - 12\n  - '34'\n  day2:\n  - ab\n  - null\n")
+        .unwrap();
+    assert_eq!(Check::Match, answers.check("day1", 0, &Answer::Integer(12)));
+    assert_eq!(Check::Mismatch(Answer::Integer(34)), answers.check("day1", 1, &35.into()));
+    assert_eq!(Check::Match, answers.check("day2", 0, &"ab".into()));
+    assert_eq!(Check::NoAnswer, answers.check("day2", 1, &Answer::None));
+    assert_eq!(Check::Missing, answers.check("day3", 0, &12.into()));
+    assert_eq!(Check::Error, answers.check("day1", 0, &Answer::Error("overflow".into())));
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut answers = Answers::default();
+    answers.days.insert("day25".to_string(), vec!["122".into(), "".into()]);
+    let yaml = serde_yaml::to_string(&answers).unwrap();
+    assert_eq!("version: 2\ndays:\n  day25:\n  - '122'\n  - ''\n", yaml);
+    let read: Answers = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(Check::Match, read.check("day25", 0, &"122".into()));
+    assert_eq!(Some(&["122".into(), "".into()][..]), read.get("day25"));
+  }
+}
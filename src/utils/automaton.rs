@@ -0,0 +1,591 @@
+//! Cellular automata on the plane.
+//!
+//! An `Automaton` is made from three parts that can be picked separately:
+//! a `Storage` that holds the cells, either densely in a grid or sparsely in
+//! a map; a `Neighborhood` that says which cells a cell can see; and an
+//! `Update` policy that says whether the cells change together or one at a
+//! time. The rules themselves are closures passed to `step`, which changes
+//! each cell's state, or `step_moves`, which moves the live cells around.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+use crate::utils::geom::{Dir4, Dir8, Point2};
+use crate::utils::grid::Grid;
+
+/// The location of a cell.
+pub type Position = Point2<i32>;
+
+const fn point((x, y): (isize, isize)) -> Position {
+  Position::new(x as i32, y as i32)
+}
+
+/// A quick hash for positions. The maps of positions are hashed several
+/// times per cell each generation and never see untrusted keys, so the
+/// standard library's flood-resistant hash costs more than it is worth.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct PositionHasher(u64);
+
+impl Hasher for PositionHasher {
+  fn finish(&self) -> u64 {
+    self.0
+  }
+
+  fn write(&mut self, bytes: &[u8]) {
+    for &b in bytes {
+      self.write_u64(b as u64);
+    }
+  }
+
+  fn write_u32(&mut self, n: u32) {
+    self.write_u64(n as u64);
+  }
+
+  fn write_i32(&mut self, n: i32) {
+    self.write_u64(n as u32 as u64);
+  }
+
+  fn write_u64(&mut self, n: u64) {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+    self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(SEED);
+  }
+}
+
+type PositionMap<V> = HashMap<Position, V, BuildHasherDefault<PositionHasher>>;
+type PositionSet = HashSet<Position, BuildHasherDefault<PositionHasher>>;
+
+/// Where the cells are kept. Every cell that isn't stored is empty, which is
+/// the cell type's default value.
+pub trait Storage: Clone {
+  type Cell: Copy + Default + PartialEq;
+
+  /// Get the state of the cell.
+  fn get(&self, pos: Position) -> Self::Cell;
+
+  /// Change the state of the cell.
+  fn set(&mut self, pos: Position, cell: Self::Cell);
+
+  /// The cells that aren't empty.
+  fn live(&self) -> impl Iterator<Item=(Position, Self::Cell)>;
+
+  /// The cells that the rule needs to be applied to, because they may change.
+  fn candidates<N: Neighborhood>(&self, neighborhood: &N) -> Vec<Position>;
+
+  /// Empty every cell, keeping the space to refill it.
+  fn clear(&mut self);
+}
+
+/// A rectangle of cells, which is best when most of the area is in use.
+/// Reads outside of the rectangle see empty cells.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Dense<C> {
+  origin: Position,
+  grid: Grid<C>,
+}
+
+impl<C: Clone + Default> Dense<C> {
+  /// Create an empty rectangle of the given size with its top left corner
+  /// at `origin`.
+  pub fn new(origin: Position, width: usize, height: usize) -> Self {
+    Dense{origin, grid: Grid::new(width, height, C::default())}
+  }
+}
+
+impl<C> Dense<C> {
+  /// Store the grid with its top left corner at `origin`.
+  pub fn from_grid(origin: Position, grid: Grid<C>) -> Self {
+    Dense{origin, grid}
+  }
+
+  pub fn grid(&self) -> &Grid<C> {
+    &self.grid
+  }
+
+  pub fn origin(&self) -> Position {
+    self.origin
+  }
+}
+
+impl<C: Copy + Default + PartialEq> Storage for Dense<C> {
+  type Cell = C;
+
+  fn get(&self, pos: Position) -> C {
+    self.grid.get(pos.x - self.origin.x, pos.y - self.origin.y).copied().unwrap_or_default()
+  }
+
+  fn set(&mut self, pos: Position, cell: C) {
+    *self.grid.get_mut(pos.x - self.origin.x, pos.y - self.origin.y)
+      .unwrap_or_else(|| panic!("{pos} is outside of the grid")) = cell;
+  }
+
+  fn live(&self) -> impl Iterator<Item=(Position, C)> {
+    self.grid.iter().filter(|(_, &cell)| cell != C::default())
+      .map(|((x, y), &cell)| (self.origin + Position::new(x as i32, y as i32), cell))
+  }
+
+  fn candidates<N: Neighborhood>(&self, _neighborhood: &N) -> Vec<Position> {
+    self.grid.positions().map(|(x, y)| self.origin + Position::new(x as i32, y as i32))
+      .collect()
+  }
+
+  fn clear(&mut self) {
+    self.grid.fill(C::default());
+  }
+}
+
+/// Only the live cells, which is best when they are spread out or the
+/// area that they cover grows.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct Sparse<C> {
+  cells: PositionMap<C>,
+}
+
+impl<C: Copy + Default + PartialEq> Sparse<C> {
+  pub fn new() -> Self {
+    Sparse{cells: PositionMap::default()}
+  }
+
+  /// The number of live cells.
+  pub fn len(&self) -> usize {
+    self.cells.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.cells.is_empty()
+  }
+}
+
+impl<C: Copy + Default + PartialEq> FromIterator<(Position, C)> for Sparse<C> {
+  fn from_iter<I: IntoIterator<Item=(Position, C)>>(iter: I) -> Self {
+    let mut result = Sparse::new();
+    for (pos, cell) in iter {
+      result.set(pos, cell);
+    }
+    result
+  }
+}
+
+impl<C: Copy + Default + PartialEq> Storage for Sparse<C> {
+  type Cell = C;
+
+  fn get(&self, pos: Position) -> C {
+    self.cells.get(&pos).copied().unwrap_or_default()
+  }
+
+  fn set(&mut self, pos: Position, cell: C) {
+    if cell == C::default() {
+      self.cells.remove(&pos);
+    } else {
+      self.cells.insert(pos, cell);
+    }
+  }
+
+  fn live(&self) -> impl Iterator<Item=(Position, C)> {
+    self.cells.iter().map(|(&pos, &cell)| (pos, cell))
+  }
+
+  /// Only the live cells and the cells next to them can change.
+  fn candidates<N: Neighborhood>(&self, neighborhood: &N) -> Vec<Position> {
+    let mut result = PositionSet::with_capacity_and_hasher(self.cells.len() * 2,
+                                                           Default::default());
+    for &pos in self.cells.keys() {
+      result.insert(pos);
+      result.extend(neighborhood.offsets().iter().map(|&d| neighborhood.wrap(pos + d)));
+    }
+    result.into_iter().collect()
+  }
+
+  /// The map keeps its room, since the next generation usually has about
+  /// as many cells.
+  fn clear(&mut self) {
+    self.cells.clear();
+  }
+}
+
+/// Which cells a cell can see. The rules get the neighbors' states in the
+/// order of the offsets.
+pub trait Neighborhood {
+  /// The offsets from a cell to its neighbors.
+  fn offsets(&self) -> &[Position];
+
+  /// Move a neighbor's position back into the world, for worlds that wrap.
+  fn wrap(&self, pos: Position) -> Position {
+    pos
+  }
+}
+
+/// The eight surrounding cells, in the order of `Dir8::ALL`.
+#[derive(Clone,Copy,Debug)]
+pub struct Moore;
+
+impl Moore {
+  const OFFSETS: [Position; 8] =
+    [point(Dir8::North.offset()), point(Dir8::NorthEast.offset()), point(Dir8::East.offset()),
+     point(Dir8::SouthEast.offset()), point(Dir8::South.offset()),
+     point(Dir8::SouthWest.offset()), point(Dir8::West.offset()),
+     point(Dir8::NorthWest.offset())];
+}
+
+impl Neighborhood for Moore {
+  fn offsets(&self) -> &[Position] {
+    &Self::OFFSETS
+  }
+}
+
+/// The four orthogonal cells, in the order of `Dir4::ALL`.
+#[derive(Clone,Copy,Debug)]
+pub struct VonNeumann;
+
+impl VonNeumann {
+  const OFFSETS: [Position; 4] =
+    [point(Dir4::North.offset()), point(Dir4::East.offset()), point(Dir4::South.offset()),
+     point(Dir4::West.offset())];
+}
+
+impl Neighborhood for VonNeumann {
+  fn offsets(&self) -> &[Position] {
+    &Self::OFFSETS
+  }
+}
+
+/// Another neighborhood on a rectangle whose opposite edges are joined, so
+/// that leaving one side comes back in the other.
+#[derive(Clone,Copy,Debug)]
+pub struct Torus<N> {
+  neighborhood: N,
+  origin: Position,
+  size: Position,
+}
+
+impl<N: Neighborhood> Torus<N> {
+  pub fn new(neighborhood: N, origin: Position, width: usize, height: usize) -> Self {
+    Torus{neighborhood, origin, size: Position::new(width as i32, height as i32)}
+  }
+}
+
+impl<N: Neighborhood> Neighborhood for Torus<N> {
+  fn offsets(&self) -> &[Position] {
+    self.neighborhood.offsets()
+  }
+
+  fn wrap(&self, pos: Position) -> Position {
+    let pos = self.neighborhood.wrap(pos) - self.origin;
+    self.origin + Position::new(wrap_coordinate(pos.x, self.size.x),
+                                wrap_coordinate(pos.y, self.size.y))
+  }
+}
+
+/// Bring a coordinate back into `0..size`. Most neighbors are already
+/// inside, so skip the division for them.
+fn wrap_coordinate(value: i32, size: i32) -> i32 {
+  if (0..size).contains(&value) { value } else { value.rem_euclid(size) }
+}
+
+/// How the rule is applied to the cells.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Update {
+  /// Every cell sees the previous generation, so they all change at once.
+  Synchronous,
+  /// The cells change one at a time in reading order, and each sees the
+  /// cells that changed before it.
+  Sequential,
+}
+
+/// A cell's request to move from one spot to another.
+#[derive(Clone,Copy,Debug)]
+struct Move<C> {
+  from: Position,
+  to: Position,
+  cell: C,
+  /// Whether the move can still happen.
+  allowed: bool,
+}
+
+/// The buffers that `step_moves` reuses from one generation to the next,
+/// so that it doesn't allocate them again each time.
+#[derive(Clone,Debug)]
+struct Moves<C> {
+  /// The live cells at the start of the generation.
+  live: Vec<(Position, C)>,
+  /// The moves that the cells asked for.
+  proposed: Vec<Move<C>>,
+  /// For each destination, the move that wants it or None if several do.
+  claims: PositionMap<Option<usize>>,
+  /// The positions that cells asked to leave.
+  origins: PositionSet,
+  /// The moves that were stopped, whose cells block anyone moving in.
+  blocked: Vec<usize>,
+}
+
+impl<C> Default for Moves<C> {
+  fn default() -> Self {
+    Moves{live: Vec::new(), proposed: Vec::new(), claims: PositionMap::default(),
+          origins: PositionSet::default(), blocked: Vec::new()}
+  }
+}
+
+/// A world of cells that changes one generation at a time.
+#[derive(Clone,Debug)]
+pub struct Automaton<S: Storage, N> {
+  cells: S,
+  neighborhood: N,
+  update: Update,
+  generation: usize,
+  moves: Moves<S::Cell>,
+  /// The storage of the generation before last, which a synchronous `step`
+  /// refills instead of allocating a new one.
+  spare: Option<S>,
+}
+
+impl<S: Storage, N: Neighborhood> Automaton<S, N> {
+  pub fn new(cells: S, neighborhood: N, update: Update) -> Self {
+    Automaton{cells, neighborhood, update, generation: 0, moves: Moves::default(),
+              spare: None}
+  }
+
+  pub fn cells(&self) -> &S {
+    &self.cells
+  }
+
+  /// The number of steps taken so far.
+  pub fn generation(&self) -> usize {
+    self.generation
+  }
+
+  /// The positions that `step` will apply the rule to, in the order that it
+  /// applies it.
+  fn candidates(&self) -> Vec<Position> {
+    let mut result = self.cells.candidates(&self.neighborhood);
+    if self.update == Update::Sequential {
+      result.sort_unstable_by_key(|pos| (pos.y, pos.x));
+    }
+    result
+  }
+
+  /// Fill the buffer with the states of the cell's neighbors.
+  fn look(cells: &S, neighborhood: &N, pos: Position, buffer: &mut Vec<S::Cell>) {
+    buffer.clear();
+    buffer.extend(neighborhood.offsets().iter().map(|&d| cells.get(neighborhood.wrap(pos + d))));
+  }
+
+  /// Compute the next generation by giving the rule each cell's position,
+  /// state, and its neighbors' states and storing the state it returns.
+  /// Returns whether any cell changed.
+  pub fn step<R>(&mut self, mut rule: R) -> bool
+      where R: FnMut(Position, S::Cell, &[S::Cell]) -> S::Cell {
+    let mut changed = false;
+    let mut neighbors = Vec::with_capacity(self.neighborhood.offsets().len());
+    let candidates = self.candidates();
+    match self.update {
+      Update::Synchronous => {
+        let mut next = self.spare.take().unwrap_or_else(|| self.cells.clone());
+        next.clear();
+        for pos in candidates {
+          let cell = self.cells.get(pos);
+          Self::look(&self.cells, &self.neighborhood, pos, &mut neighbors);
+          let new_cell = rule(pos, cell, &neighbors);
+          changed |= new_cell != cell;
+          next.set(pos, new_cell);
+        }
+        self.spare = Some(std::mem::replace(&mut self.cells, next));
+      }
+      Update::Sequential => {
+        for pos in candidates {
+          let cell = self.cells.get(pos);
+          Self::look(&self.cells, &self.neighborhood, pos, &mut neighbors);
+          let new_cell = rule(pos, cell, &neighbors);
+          if new_cell != cell {
+            changed = true;
+            self.cells.set(pos, new_cell);
+          }
+        }
+      }
+    }
+    self.generation += 1;
+    changed
+  }
+
+  /// Move the live cells. The rule gets each live cell's position, state,
+  /// and neighbors' states and picks where it wants to go, if anywhere.
+  /// When the update is synchronous, cells that want the same spot all stay
+  /// put, as does a cell whose spot is held by a cell that isn't leaving.
+  /// When it is sequential, each moves in turn if its spot is empty.
+  /// Returns whether any cell moved.
+  pub fn step_moves<R>(&mut self, mut propose: R) -> bool
+      where R: FnMut(Position, S::Cell, &[S::Cell]) -> Option<Position> {
+    let mut moved = false;
+    let mut neighbors = Vec::with_capacity(self.neighborhood.offsets().len());
+    let moves = &mut self.moves;
+    moves.live.clear();
+    moves.live.extend(self.cells.live());
+    match self.update {
+      Update::Synchronous => {
+        moves.proposed.clear();
+        moves.claims.clear();
+        moves.origins.clear();
+        moves.blocked.clear();
+        for &(pos, cell) in &moves.live {
+          Self::look(&self.cells, &self.neighborhood, pos, &mut neighbors);
+          let dest = propose(pos, cell, &neighbors).map(|d| self.neighborhood.wrap(d));
+          if let Some(dest) = dest.filter(|&dest| dest != pos) {
+            let index = moves.proposed.len();
+            moves.claims.entry(dest).and_modify(|claim| *claim = None).or_insert(Some(index));
+            moves.origins.insert(pos);
+            moves.proposed.push(Move{from: pos, to: dest, cell, allowed: true});
+          }
+        }
+        // stop the moves into contested spots or spots that stay occupied
+        for (index, mv) in moves.proposed.iter_mut().enumerate() {
+          if moves.claims[&mv.to].is_none()
+              || (self.cells.get(mv.to) != S::Cell::default() && !moves.origins.contains(&mv.to)) {
+            mv.allowed = false;
+            moves.blocked.push(index);
+          }
+        }
+        // a cell that stays put blocks any move into its spot
+        while let Some(index) = moves.blocked.pop() {
+          if let Some(&Some(other)) = moves.claims.get(&moves.proposed[index].from) {
+            if moves.proposed[other].allowed {
+              moves.proposed[other].allowed = false;
+              moves.blocked.push(other);
+            }
+          }
+        }
+        // empty all of the spots that are left before filling the new ones
+        for mv in moves.proposed.iter().filter(|mv| mv.allowed) {
+          self.cells.set(mv.from, S::Cell::default());
+        }
+        for mv in moves.proposed.iter().filter(|mv| mv.allowed) {
+          moved = true;
+          self.cells.set(mv.to, mv.cell);
+        }
+      }
+      Update::Sequential => {
+        moves.live.sort_unstable_by_key(|(pos, _)| (pos.y, pos.x));
+        for &(pos, cell) in &moves.live {
+          Self::look(&self.cells, &self.neighborhood, pos, &mut neighbors);
+          if let Some(dest) = propose(pos, cell, &neighbors).map(|d| self.neighborhood.wrap(d)) {
+            if self.cells.get(dest) == S::Cell::default() {
+              moved = true;
+              self.cells.set(pos, S::Cell::default());
+              self.cells.set(dest, cell);
+            }
+          }
+        }
+      }
+    }
+    self.generation += 1;
+    moved
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::automaton::{Automaton, Dense, Moore, Neighborhood, Position, Sparse,
+                                Storage, Torus, Update, VonNeumann};
+  use crate::utils::grid::Grid;
+
+  fn glider() -> Sparse<bool> {
+    [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter()
+      .map(|&(x, y)| (Position::new(x, y), true)).collect()
+  }
+
+  fn life(_: Position, alive: bool, neighbors: &[bool]) -> bool {
+    let count = neighbors.iter().filter(|&&n| n).count();
+    count == 3 || (alive && count == 2)
+  }
+
+  fn sorted<S: Storage>(cells: &S) -> Vec<Position> {
+    let mut result: Vec<Position> = cells.live().map(|(pos, _)| pos).collect();
+    result.sort_unstable_by_key(|pos| (pos.y, pos.x));
+    result
+  }
+
+  #[test]
+  fn test_sparse_life() {
+    let mut life_game = Automaton::new(glider(), Moore, Update::Synchronous);
+    for _ in 0..4 {
+      assert!(life_game.step(life));
+    }
+    assert_eq!(4, life_game.generation());
+    // after four generations the glider has moved down and right
+    let moved: Vec<Position> = sorted(&glider()).iter().map(|&p| p + Position::new(1, 1))
+      .collect();
+    assert_eq!(moved, sorted(life_game.cells()));
+  }
+
+  #[test]
+  fn test_dense_torus() {
+    let grid = Grid::parse(".#...\n..#..\n###..\n.....\n.....", |ch| ch == '#');
+    let torus = Torus::new(Moore, Position::new(0, 0), 5, 5);
+    assert_eq!(Position::new(4, 0), torus.wrap(Position::new(-1, 5)));
+    let mut life_game = Automaton::new(Dense::from_grid(Position::new(0, 0), grid.clone()),
+                                       torus, Update::Synchronous);
+    // the glider comes back to where it started after crossing the torus
+    for _ in 0..20 {
+      life_game.step(life);
+    }
+    assert_eq!(&grid, life_game.cells().grid());
+  }
+
+  #[test]
+  fn test_sequential() {
+    // a cell that copies its west neighbor spreads all the way in one pass
+    let mut cells = Dense::new(Position::new(-2, 0), 5, 1);
+    cells.set(Position::new(-2, 0), 1);
+    let mut automaton = Automaton::new(cells.clone(), VonNeumann, Update::Sequential);
+    assert!(automaton.step(|_, cell, neighbors| cell.max(neighbors[3])));
+    assert_eq!(&[1, 1, 1, 1, 1], automaton.cells().grid().row(0));
+    // while the synchronous update moves one cell per step
+    let mut automaton = Automaton::new(cells, VonNeumann, Update::Synchronous);
+    automaton.step(|_, cell, neighbors| cell.max(neighbors[3]));
+    assert_eq!(&[1, 1, 0, 0, 0], automaton.cells().grid().row(0));
+  }
+
+  #[test]
+  fn test_moves() {
+    let cells: Sparse<char> = [(Position::new(0, 0), 'a'), (Position::new(2, 0), 'b'),
+                               (Position::new(0, 2), 'c')].into_iter().collect();
+    // everyone heads for (1, 0), so a and b collide, while c moves up
+    let toward = |pos: Position, _, _: &[char]| Some(pos + Position::new(
+      (1 - pos.x).signum(), (-pos.y).signum()));
+    let mut automaton = Automaton::new(cells.clone(), VonNeumann, Update::Synchronous);
+    assert!(automaton.step_moves(toward));
+    assert_eq!('a', automaton.cells().get(Position::new(0, 0)));
+    assert_eq!('b', automaton.cells().get(Position::new(2, 0)));
+    assert_eq!('c', automaton.cells().get(Position::new(1, 1)));
+    // one at a time, a gets there first and the others are blocked or move
+    let mut automaton = Automaton::new(cells, VonNeumann, Update::Sequential);
+    assert!(automaton.step_moves(toward));
+    assert_eq!('a', automaton.cells().get(Position::new(1, 0)));
+    assert_eq!('b', automaton.cells().get(Position::new(2, 0)));
+    assert_eq!('c', automaton.cells().get(Position::new(1, 1)));
+    assert!(!automaton.step_moves(|_, _, _| None));
+    assert_eq!(3, automaton.cells().len());
+  }
+
+  #[test]
+  fn test_blocked_moves() {
+    // a wants b's spot, b wants c's, and c stays, so nobody can move
+    let cells: Sparse<char> = [(Position::new(0, 0), 'a'), (Position::new(1, 0), 'b'),
+                               (Position::new(2, 0), 'c')].into_iter().collect();
+    let right = |pos: Position, cell, _: &[char]|
+      (cell != 'c').then(|| pos + Position::new(1, 0));
+    let mut automaton = Automaton::new(cells.clone(), VonNeumann, Update::Synchronous);
+    assert!(!automaton.step_moves(right));
+    assert_eq!(cells, *automaton.cells());
+    // once c is out of the way, the whole line moves along
+    assert!(automaton.step_moves(|pos, _, _| Some(pos + Position::new(1, 0))));
+    assert_eq!("abc", (1..4).map(|x| automaton.cells().get(Position::new(x, 0)))
+      .collect::<String>());
+    assert_eq!(3, automaton.cells().len());
+    // two cells can swap places
+    let swap = |pos: Position, _, _: &[char]| Some(Position::new(3 - pos.x, 0));
+    let mut pair = Automaton::new([(Position::new(1, 0), 'a'), (Position::new(2, 0), 'b')]
+                                    .into_iter().collect::<Sparse<char>>(),
+                                  VonNeumann, Update::Synchronous);
+    assert!(pair.step_moves(swap));
+    assert_eq!('b', pair.cells().get(Position::new(1, 0)));
+    assert_eq!('a', pair.cells().get(Position::new(2, 0)));
+  }
+}
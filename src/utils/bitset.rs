@@ -0,0 +1,449 @@
+//! Sets of small integers stored as bits.
+//!
+//! `BitSet<N>` has a fixed capacity of `64 * N` bits and is `Copy`, so it is
+//! cheap to use in search states and grid rows. `GrowableBitSet` grows to hold
+//! whatever is inserted into it.
+
+use serde::{Serialize, Serializer};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Shl, Shr, Sub, SubAssign};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Iterate through the positions of the set bits in increasing order.
+#[derive(Clone,Debug)]
+pub struct Ones<'a> {
+  words: &'a [u64],
+  /// the index of the word in current
+  index: usize,
+  /// the bits of the current word that haven't been returned yet
+  current: u64,
+}
+
+impl<'a> Ones<'a> {
+  fn new(words: &'a [u64]) -> Self {
+    Ones{words, index: 0, current: words.first().copied().unwrap_or(0)}
+  }
+}
+
+impl Iterator for Ones<'_> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    while self.current == 0 {
+      self.index += 1;
+      self.current = *self.words.get(self.index)?;
+    }
+    let bit = self.current.trailing_zeros() as usize;
+    self.current &= self.current - 1;
+    Some(self.index * WORD_BITS + bit)
+  }
+}
+
+fn count_ones(words: &[u64]) -> usize {
+  words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+/// Move the bits from `words` up by `shift` into `out`, dropping any that
+/// don't fit.
+fn shift_left(words: &[u64], shift: usize, out: &mut [u64]) {
+  let (word_shift, bit_shift) = (shift / WORD_BITS, shift % WORD_BITS);
+  for (i, word) in out.iter_mut().enumerate().skip(word_shift) {
+    let src = i - word_shift;
+    let low = words.get(src).copied().unwrap_or(0) << bit_shift;
+    let carry = if bit_shift > 0 && src > 0 {
+      words.get(src - 1).copied().unwrap_or(0) >> (WORD_BITS - bit_shift)
+    } else {
+      0
+    };
+    *word = low | carry;
+  }
+}
+
+/// Move the bits from `words` down by `shift` into `out`, dropping the ones
+/// that go below zero.
+fn shift_right(words: &[u64], shift: usize, out: &mut [u64]) {
+  let (word_shift, bit_shift) = (shift / WORD_BITS, shift % WORD_BITS);
+  for (i, word) in out.iter_mut().enumerate() {
+    let src = i + word_shift;
+    let high = words.get(src).copied().unwrap_or(0) >> bit_shift;
+    let carry = if bit_shift > 0 {
+      words.get(src + 1).copied().unwrap_or(0) << (WORD_BITS - bit_shift)
+    } else {
+      0
+    };
+    *word = high | carry;
+  }
+}
+
+/// A set with room for `64 * N` values.
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub struct BitSet<const N: usize> {
+  words: [u64; N],
+}
+
+impl<const N: usize> BitSet<N> {
+  pub const CAPACITY: usize = N * WORD_BITS;
+
+  pub const fn new() -> Self {
+    BitSet{words: [0; N]}
+  }
+
+  fn check(posn: usize) {
+    assert!(posn < Self::CAPACITY, "bit {posn} is past the capacity of {}", Self::CAPACITY);
+  }
+
+  pub fn insert(&mut self, posn: usize) {
+    Self::check(posn);
+    self.words[posn / WORD_BITS] |= 1 << (posn % WORD_BITS);
+  }
+
+  pub fn remove(&mut self, posn: usize) {
+    Self::check(posn);
+    self.words[posn / WORD_BITS] &= !(1 << (posn % WORD_BITS));
+  }
+
+  pub fn contains(&self, posn: usize) -> bool {
+    posn < Self::CAPACITY && self.words[posn / WORD_BITS] & (1 << (posn % WORD_BITS)) != 0
+  }
+
+  /// The number of values in the set.
+  pub fn len(&self) -> usize {
+    count_ones(&self.words)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.iter().all(|&w| w == 0)
+  }
+
+  pub fn iter(&self) -> Ones<'_> {
+    Ones::new(&self.words)
+  }
+
+  /// The smallest value in the set.
+  pub fn first(&self) -> Option<usize> {
+    self.iter().next()
+  }
+
+  pub fn is_disjoint(&self, other: &Self) -> bool {
+    self.words.iter().zip(other.words.iter()).all(|(l, r)| l & r == 0)
+  }
+}
+
+impl<const N: usize> Default for BitSet<N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Use the bits of the value as the lowest 64 values of the set.
+impl<const N: usize> From<u64> for BitSet<N> {
+  fn from(bits: u64) -> Self {
+    let mut result = Self::new();
+    result.words[0] = bits;
+    result
+  }
+}
+
+impl<const N: usize> Extend<usize> for BitSet<N> {
+  fn extend<I: IntoIterator<Item=usize>>(&mut self, iter: I) {
+    for posn in iter {
+      self.insert(posn);
+    }
+  }
+}
+
+impl<const N: usize> FromIterator<usize> for BitSet<N> {
+  fn from_iter<I: IntoIterator<Item=usize>>(iter: I) -> Self {
+    let mut result = Self::new();
+    result.extend(iter);
+    result
+  }
+}
+
+impl<const N: usize> BitOrAssign for BitSet<N> {
+  fn bitor_assign(&mut self, other: Self) {
+    self.words.iter_mut().zip(other.words).for_each(|(l, r)| *l |= r);
+  }
+}
+
+impl<const N: usize> BitAndAssign for BitSet<N> {
+  fn bitand_assign(&mut self, other: Self) {
+    self.words.iter_mut().zip(other.words).for_each(|(l, r)| *l &= r);
+  }
+}
+
+impl<const N: usize> SubAssign for BitSet<N> {
+  fn sub_assign(&mut self, other: Self) {
+    self.words.iter_mut().zip(other.words).for_each(|(l, r)| *l &= !r);
+  }
+}
+
+impl<const N: usize> BitOr for BitSet<N> {
+  type Output = Self;
+
+  fn bitor(mut self, other: Self) -> Self {
+    self |= other;
+    self
+  }
+}
+
+impl<const N: usize> BitAnd for BitSet<N> {
+  type Output = Self;
+
+  fn bitand(mut self, other: Self) -> Self {
+    self &= other;
+    self
+  }
+}
+
+impl<const N: usize> Sub for BitSet<N> {
+  type Output = Self;
+
+  fn sub(mut self, other: Self) -> Self {
+    self -= other;
+    self
+  }
+}
+
+/// Add the shift to each value, dropping the ones past the capacity.
+impl<const N: usize> Shl<usize> for BitSet<N> {
+  type Output = Self;
+
+  fn shl(self, shift: usize) -> Self {
+    let mut result = Self::new();
+    shift_left(&self.words, shift, &mut result.words);
+    result
+  }
+}
+
+/// Subtract the shift from each value, dropping the ones below zero.
+impl<const N: usize> Shr<usize> for BitSet<N> {
+  type Output = Self;
+
+  fn shr(self, shift: usize) -> Self {
+    let mut result = Self::new();
+    shift_right(&self.words, shift, &mut result.words);
+    result
+  }
+}
+
+impl<const N: usize> Serialize for BitSet<N> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.iter())
+  }
+}
+
+/// A set that grows to fit the values in it.
+#[derive(Clone,Debug,Default,Eq,Hash,PartialEq)]
+pub struct GrowableBitSet {
+  /// Trailing zero words are always trimmed, so that equal sets have equal
+  /// representations.
+  words: Vec<u64>,
+}
+
+impl GrowableBitSet {
+  pub fn new() -> Self {
+    GrowableBitSet{words: Vec::new()}
+  }
+
+  fn trim(&mut self) {
+    while self.words.last() == Some(&0) {
+      self.words.pop();
+    }
+  }
+
+  pub fn insert(&mut self, posn: usize) {
+    let word = posn / WORD_BITS;
+    if word >= self.words.len() {
+      self.words.resize(word + 1, 0);
+    }
+    self.words[word] |= 1 << (posn % WORD_BITS);
+  }
+
+  pub fn remove(&mut self, posn: usize) {
+    if let Some(word) = self.words.get_mut(posn / WORD_BITS) {
+      *word &= !(1 << (posn % WORD_BITS));
+      self.trim();
+    }
+  }
+
+  pub fn contains(&self, posn: usize) -> bool {
+    self.words.get(posn / WORD_BITS).is_some_and(|w| w & (1 << (posn % WORD_BITS)) != 0)
+  }
+
+  /// The number of values in the set.
+  pub fn len(&self) -> usize {
+    count_ones(&self.words)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.is_empty()
+  }
+
+  pub fn iter(&self) -> Ones<'_> {
+    Ones::new(&self.words)
+  }
+
+  /// The smallest value in the set.
+  pub fn first(&self) -> Option<usize> {
+    self.iter().next()
+  }
+
+  pub fn is_disjoint(&self, other: &Self) -> bool {
+    self.words.iter().zip(other.words.iter()).all(|(l, r)| l & r == 0)
+  }
+
+  fn shifted_left(&self, shift: usize) -> Self {
+    if self.is_empty() {
+      return Self::new()
+    }
+    let mut words = vec![0; self.words.len() + shift.div_ceil(WORD_BITS)];
+    shift_left(&self.words, shift, &mut words);
+    let mut result = GrowableBitSet{words};
+    result.trim();
+    result
+  }
+
+  fn shifted_right(&self, shift: usize) -> Self {
+    let mut words = vec![0; self.words.len().saturating_sub(shift / WORD_BITS)];
+    shift_right(&self.words, shift, &mut words);
+    let mut result = GrowableBitSet{words};
+    result.trim();
+    result
+  }
+}
+
+impl Extend<usize> for GrowableBitSet {
+  fn extend<I: IntoIterator<Item=usize>>(&mut self, iter: I) {
+    for posn in iter {
+      self.insert(posn);
+    }
+  }
+}
+
+impl FromIterator<usize> for GrowableBitSet {
+  fn from_iter<I: IntoIterator<Item=usize>>(iter: I) -> Self {
+    let mut result = Self::new();
+    result.extend(iter);
+    result
+  }
+}
+
+impl BitOrAssign<&GrowableBitSet> for GrowableBitSet {
+  fn bitor_assign(&mut self, other: &GrowableBitSet) {
+    if other.words.len() > self.words.len() {
+      self.words.resize(other.words.len(), 0);
+    }
+    self.words.iter_mut().zip(other.words.iter()).for_each(|(l, r)| *l |= r);
+  }
+}
+
+impl BitAndAssign<&GrowableBitSet> for GrowableBitSet {
+  fn bitand_assign(&mut self, other: &GrowableBitSet) {
+    self.words.truncate(other.words.len());
+    self.words.iter_mut().zip(other.words.iter()).for_each(|(l, r)| *l &= r);
+    self.trim();
+  }
+}
+
+impl SubAssign<&GrowableBitSet> for GrowableBitSet {
+  fn sub_assign(&mut self, other: &GrowableBitSet) {
+    self.words.iter_mut().zip(other.words.iter()).for_each(|(l, r)| *l &= !r);
+    self.trim();
+  }
+}
+
+impl BitOr for &GrowableBitSet {
+  type Output = GrowableBitSet;
+
+  fn bitor(self, other: Self) -> GrowableBitSet {
+    let mut result = self.clone();
+    result |= other;
+    result
+  }
+}
+
+impl BitAnd for &GrowableBitSet {
+  type Output = GrowableBitSet;
+
+  fn bitand(self, other: Self) -> GrowableBitSet {
+    let mut result = self.clone();
+    result &= other;
+    result
+  }
+}
+
+impl Sub for &GrowableBitSet {
+  type Output = GrowableBitSet;
+
+  fn sub(self, other: Self) -> GrowableBitSet {
+    let mut result = self.clone();
+    result -= other;
+    result
+  }
+}
+
+/// Add the shift to each value.
+impl Shl<usize> for &GrowableBitSet {
+  type Output = GrowableBitSet;
+
+  fn shl(self, shift: usize) -> GrowableBitSet {
+    self.shifted_left(shift)
+  }
+}
+
+/// Subtract the shift from each value, dropping the ones below zero.
+impl Shr<usize> for &GrowableBitSet {
+  type Output = GrowableBitSet;
+
+  fn shr(self, shift: usize) -> GrowableBitSet {
+    self.shifted_right(shift)
+  }
+}
+
+impl Serialize for GrowableBitSet {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.iter())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::bitset::{BitSet, GrowableBitSet};
+
+  #[test]
+  fn test_fixed() {
+    let left: BitSet<2> = [1, 5, 63, 64, 100].into_iter().collect();
+    let right: BitSet<2> = [5, 64, 127].into_iter().collect();
+    assert_eq!(128, BitSet::<2>::CAPACITY);
+    assert_eq!(5, left.len());
+    assert!(left.contains(63) && !left.contains(62) && !left.contains(500));
+    assert_eq!(vec![1, 5, 63, 64, 100, 127], (left | right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![5, 64], (left & right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![1, 63, 100], (left - right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![3, 7, 65, 66, 102], (left << 2).iter().collect::<Vec<_>>());
+    assert_eq!(vec![0, 36], (left >> 64).iter().collect::<Vec<_>>());
+    assert_eq!(vec![0, 4, 62, 63, 99], (left >> 1).iter().collect::<Vec<_>>());
+    assert_eq!(Some(1), left.first());
+    assert!(!left.is_disjoint(&right));
+    assert_eq!(vec![0, 2], BitSet::<1>::from(5).iter().collect::<Vec<_>>());
+    assert!(BitSet::<1>::new().is_empty());
+  }
+
+  #[test]
+  fn test_growable() {
+    let mut left: GrowableBitSet = [3, 200].into_iter().collect();
+    let right: GrowableBitSet = [3, 70].into_iter().collect();
+    assert_eq!(vec![3, 70, 200], (&left | &right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![3], (&left & &right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![200], (&left - &right).iter().collect::<Vec<_>>());
+    assert_eq!(vec![133, 330], (&left << 130).iter().collect::<Vec<_>>());
+    assert_eq!(vec![0, 197], (&left >> 3).iter().collect::<Vec<_>>());
+    left.remove(200);
+    assert_eq!(&left & &right, [3].into_iter().collect());
+    assert_eq!(1, left.len());
+    left.remove(3);
+    assert_eq!(GrowableBitSet::new(), left);
+    assert!(left.is_empty());
+  }
+}
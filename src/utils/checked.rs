@@ -0,0 +1,175 @@
+//! Arithmetic that reports overflow instead of silently wrapping.
+//!
+//! Release builds wrap on overflow, which shows up as a wrong answer rather
+//! than an error. The solvers use these helpers for the operations that can
+//! overflow on a bad input. With the `checked-arithmetic` feature, the first
+//! overflow in each phase is recorded, and the runner turns it into an error
+//! for that part. Without the feature, they are the plain operators.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::utils::Answer;
+
+/// The integer types that the helpers work on.
+pub trait Integer: Copy + Default + fmt::Display + TryFrom<usize> + Add<Output=Self>
+    + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> {
+  const NAME: &'static str;
+  fn checked_add(self, other: Self) -> Option<Self>;
+  fn checked_sub(self, other: Self) -> Option<Self>;
+  fn checked_mul(self, other: Self) -> Option<Self>;
+  fn checked_div(self, other: Self) -> Option<Self>;
+  fn wrapping_add(self, other: Self) -> Self;
+  fn wrapping_sub(self, other: Self) -> Self;
+  fn wrapping_mul(self, other: Self) -> Self;
+  /// Convert with `as`, truncating the value if it doesn't fit.
+  fn truncating_from(value: usize) -> Self;
+}
+
+macro_rules! integer_impl {
+  ( $($t:ty),* ) => {
+    $(impl Integer for $t {
+      const NAME: &'static str = stringify!($t);
+      fn checked_add(self, other: Self) -> Option<Self> { self.checked_add(other) }
+      fn checked_sub(self, other: Self) -> Option<Self> { self.checked_sub(other) }
+      fn checked_mul(self, other: Self) -> Option<Self> { self.checked_mul(other) }
+      fn checked_div(self, other: Self) -> Option<Self> { self.checked_div(other) }
+      fn wrapping_add(self, other: Self) -> Self { self.wrapping_add(other) }
+      fn wrapping_sub(self, other: Self) -> Self { self.wrapping_sub(other) }
+      fn wrapping_mul(self, other: Self) -> Self { self.wrapping_mul(other) }
+      fn truncating_from(value: usize) -> Self { value as Self }
+    })*
+  }
+}
+
+integer_impl!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+/// Are the operations checked in this build?
+pub const ENABLED: bool = cfg!(feature = "checked-arithmetic");
+
+struct Phase {
+  label: String,
+  overflow: Option<String>,
+}
+
+thread_local! {
+  static PHASE: RefCell<Phase> = const { RefCell::new(Phase{label: String::new(),
+                                                             overflow: None}) };
+}
+
+/// Start a new phase, such as "day11 part 2", that the following operations
+/// belong to.
+pub fn start(label: &str) {
+  PHASE.with_borrow_mut(|phase| {
+    phase.label = label.to_string();
+    phase.overflow = None;
+  });
+}
+
+/// Get and clear the description of the first overflow since the phase
+/// started.
+pub fn take() -> Option<String> {
+  PHASE.with_borrow_mut(|phase| phase.overflow.take())
+}
+
+/// Replace the answer with an error if there was an overflow in this phase
+/// or an earlier one that it depends on, such as the generator.
+pub fn check(earlier: Option<String>, answer: Answer) -> Answer {
+  match earlier.or(take()) {
+    Some(message) => Answer::Error(message),
+    None => answer,
+  }
+}
+
+/// Record an overflow and return the value to carry on with.
+#[cold]
+fn overflow<T: Integer>(op: &str, detail: fmt::Arguments, result: T) -> T {
+  PHASE.with_borrow_mut(|phase| if phase.overflow.is_none() {
+    phase.overflow = Some(format!("{}: {} overflow in {op} ({detail})", phase.label, T::NAME));
+  });
+  result
+}
+
+pub fn add<T: Integer>(a: T, b: T, op: &str) -> T {
+  if ENABLED {
+    a.checked_add(b).unwrap_or_else(|| overflow(op, format_args!("{a} + {b}"), a.wrapping_add(b)))
+  } else {
+    a + b
+  }
+}
+
+pub fn sub<T: Integer>(a: T, b: T, op: &str) -> T {
+  if ENABLED {
+    a.checked_sub(b).unwrap_or_else(|| overflow(op, format_args!("{a} - {b}"), a.wrapping_sub(b)))
+  } else {
+    a - b
+  }
+}
+
+pub fn mul<T: Integer>(a: T, b: T, op: &str) -> T {
+  if ENABLED {
+    a.checked_mul(b).unwrap_or_else(|| overflow(op, format_args!("{a} * {b}"), a.wrapping_mul(b)))
+  } else {
+    a * b
+  }
+}
+
+/// Divide, which fails on a zero divisor as well as overflow. Both carry on
+/// with zero.
+pub fn div<T: Integer>(a: T, b: T, op: &str) -> T {
+  if ENABLED {
+    a.checked_div(b).unwrap_or_else(|| overflow(op, format_args!("{a} / {b}"), T::default()))
+  } else {
+    a / b
+  }
+}
+
+/// Convert a size or count into a smaller type.
+pub fn from_usize<T: Integer>(value: usize, op: &str) -> T {
+  if ENABLED {
+    T::try_from(value).unwrap_or_else(|_| overflow(op, format_args!("{value} doesn't fit"),
+                                                   T::truncating_from(value)))
+  } else {
+    T::truncating_from(value)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::Answer;
+  use crate::utils::checked::{self, add, div, from_usize, mul, sub};
+
+  #[test]
+  fn test_in_range() {
+    checked::start("day1 part 1");
+    assert_eq!(7u8, add(3, 4, "add"));
+    assert_eq!(-1i64, sub(3, 4, "sub"));
+    assert_eq!(12u64, mul(3, 4, "mul"));
+    assert_eq!(-2i32, div(-7, 3, "div"));
+    assert_eq!(200, from_usize::<u8>(200, "len"));
+    assert_eq!(None, checked::take());
+    assert_eq!(Answer::Integer(5), checked::check(None, 5.into()));
+    assert_eq!(Answer::Error("day1 generator: bad".to_string()),
+               checked::check(Some("day1 generator: bad".to_string()), 5.into()));
+  }
+
+  #[cfg(feature = "checked-arithmetic")]
+  #[test]
+  fn test_overflow() {
+    checked::start("day11 part 2");
+    assert_eq!(4u8, mul(130, 2, "Operation::Square"));
+    assert_eq!(0u8, add(255, 1, "second"));
+    assert_eq!(Some("day11 part 2: u8 overflow in Operation::Square (130 * 2)".to_string()),
+               checked::take());
+    assert_eq!(None, checked::take());
+    assert_eq!(0i64, div(5, 0, "Operation::Divide"));
+    assert_eq!(Answer::Error("day11 part 2: i64 overflow in Operation::Divide (5 / 0)"
+                             .to_string()), checked::check(None, 5.into()));
+    assert_eq!(44, from_usize::<u8>(300, "valve count"));
+    checked::start("day16 generator");
+    assert_eq!(None, checked::take());
+    assert_eq!(i64::MAX, sub(i64::MIN, 1, "sub"));
+    assert!(checked::take().unwrap().contains("i64 overflow in sub"));
+  }
+}
@@ -0,0 +1,335 @@
+//! The runner's settings, which come from the command line, environment
+//! variables, and a config file.
+//!
+//! The config file is YAML, like answers.yml, and is read from `aoc2022.yml`
+//! in the current directory or, if there isn't one, from the user's config
+//! directory. A setting on the command line wins over one in the
+//! environment, which wins over one in the file. The settings that turn
+//! something on can be turned back off by a later layer with a keyword:
+//! `all` for the day, and `none` for the dump format and the report and
+//! render destinations, such as `AOC_DAY=all` or `--html none`.
+//!
+//! The parameters can't be turned off, and since they change the answers,
+//! any parameters in the file stop every run from updating answers.yml.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::utils::DumpFormat;
+
+/// The name of the config file.
+pub const FILE_NAME: &str = "aoc2022.yml";
+
+/// The prefix of the environment variables, such as `AOC_INPUT`.
+pub const ENV_PREFIX: &str = "AOC_";
+
+/// The keyword that turns a setting off.
+const OFF: &str = "none";
+
+/// Which days to run.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Days {
+  All,
+  Only(usize),
+}
+
+impl FromStr for Days {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "all" => Ok(Days::All),
+      _ => s.parse().map(Days::Only)
+        .map_err(|_| format!("unknown day '{s}', expected a number or all")),
+    }
+  }
+}
+
+impl fmt::Display for Days {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Days::All => write!(f, "all"),
+      Days::Only(day) => write!(f, "{day}"),
+    }
+  }
+}
+
+impl Serialize for Days {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Days::All => serializer.serialize_str("all"),
+      Days::Only(day) => serializer.serialize_u64(*day as u64),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for Days {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Written {
+      Number(usize),
+      Text(String),
+    }
+    match Written::deserialize(deserializer)? {
+      Written::Number(day) => Ok(Days::Only(day)),
+      Written::Text(text) => text.parse().map_err(D::Error::custom),
+    }
+  }
+}
+
+/// Whether to print the parsed input instead of solving it.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Dump {
+  /// Solve the puzzles, which is written as `none`.
+  Off,
+  Format(DumpFormat),
+}
+
+impl FromStr for Dump {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      OFF => Ok(Dump::Off),
+      _ => s.parse().map(Dump::Format)
+        .map_err(|_| format!("unknown format '{s}', expected yaml, json, or none")),
+    }
+  }
+}
+
+impl Serialize for Dump {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      Dump::Off => serializer.serialize_str(OFF),
+      Dump::Format(format) => format.serialize(serializer),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for Dump {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+  }
+}
+
+/// A layer of settings. Each setting is only present if that layer sets it.
+#[derive(Clone,Debug,Default,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+  /// The input directory.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub input: Option<String>,
+  /// A single day to run, or all of them.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub day: Option<Days>,
+  /// Write an HTML report to this file, unless it is `none`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub html: Option<String>,
+  /// Write a JUnit XML report to this file, unless it is `none`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub junit: Option<String>,
+  /// Print the parsed input in this format instead of solving it.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub dump_input: Option<Dump>,
+  /// Where to render the frames of the simulations, unless it is `none`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub render: Option<String>,
+  /// Render one of every N frames.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub render_every: Option<usize>,
+  /// The random seed for anonymizing.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub seed: Option<u64>,
+  /// The solvers' parameters, such as `day1.top`. They can't be set from
+  /// the environment.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "parse_params")]
+  pub params: BTreeMap<String, String>,
+}
+
+/// Read the parameters, which may be written as YAML numbers or booleans,
+/// as strings.
+fn parse_params<'de, D: Deserializer<'de>>(deserializer: D)
+    -> Result<BTreeMap<String, String>, D::Error> {
+  let values: BTreeMap<String, serde_yaml::Value> = BTreeMap::deserialize(deserializer)?;
+  values.into_iter().map(|(name, value)| match value {
+    serde_yaml::Value::String(s) => Ok((name, s)),
+    serde_yaml::Value::Number(n) => Ok((name, n.to_string())),
+    serde_yaml::Value::Bool(b) => Ok((name, b.to_string())),
+    _ => Err(D::Error::custom(format!("parameter {name} must be a single value"))),
+  }).collect()
+}
+
+/// Parse an environment variable's value, if it is set.
+fn parse_var<T: FromStr>(lookup: &impl Fn(&str) -> Option<String>,
+                         name: &str) -> Result<Option<T>, String> {
+  let var = format!("{ENV_PREFIX}{}", name.to_uppercase());
+  match lookup(&var) {
+    None => Ok(None),
+    Some(value) => value.parse().map(Some)
+      .map_err(|_| format!("Can't parse {var}='{value}'")),
+  }
+}
+
+impl Config {
+  /// The settings from a config file's text.
+  pub fn parse(text: &str) -> Result<Self, String> {
+    if text.trim().is_empty() {
+      return Ok(Config::default())
+    }
+    serde_yaml::from_str(text).map_err(|e| e.to_string())
+  }
+
+  /// The settings from the environment, using the lookup to find the
+  /// variables.
+  pub fn from_env(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+    Ok(Config{
+      input: parse_var(&lookup, "input")?,
+      day: parse_var(&lookup, "day")?,
+      html: parse_var(&lookup, "html")?,
+      junit: parse_var(&lookup, "junit")?,
+      dump_input: parse_var(&lookup, "dump_input")?,
+      render: parse_var(&lookup, "render")?,
+      render_every: parse_var(&lookup, "render_every")?,
+      seed: parse_var(&lookup, "seed")?,
+      params: BTreeMap::new(),
+    })
+  }
+
+  /// Combine the layers, with the settings in `over` replacing these. The
+  /// parameters are combined one at a time.
+  pub fn merge(self, over: Config) -> Self {
+    let mut params = self.params;
+    params.extend(over.params);
+    Config{
+      input: over.input.or(self.input),
+      day: over.day.or(self.day),
+      html: over.html.or(self.html),
+      junit: over.junit.or(self.junit),
+      dump_input: over.dump_input.or(self.dump_input),
+      render: over.render.or(self.render),
+      render_every: over.render_every.or(self.render_every),
+      seed: over.seed.or(self.seed),
+      params,
+    }
+  }
+
+  /// Fill in the defaults for the settings that no layer set, and drop the
+  /// destinations that were turned off with `none`.
+  pub fn with_defaults(self) -> Self {
+    let turned_on = |dest: Option<String>| dest.filter(|d| d != OFF);
+    Config{
+      input: Some(self.input.unwrap_or_else(|| "input".to_string())),
+      html: turned_on(self.html),
+      junit: turned_on(self.junit),
+      render: turned_on(self.render),
+      render_every: Some(self.render_every.unwrap_or(1)),
+      seed: Some(self.seed.unwrap_or(1)),
+      ..self
+    }
+  }
+
+  /// Find the config file: the one in the current directory, or else the
+  /// one in the user's config directory.
+  pub fn find_file() -> Option<PathBuf> {
+    let user_dir = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+      .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    std::iter::once(PathBuf::from(FILE_NAME))
+      .chain(user_dir.map(|dir| dir.join(FILE_NAME)))
+      .find(|path| path.is_file())
+  }
+
+  /// Load the settings from the config file and the environment, and then
+  /// apply the command line's settings on top. Returns the settings with
+  /// the defaults filled in and the file that was read, if any.
+  pub fn load(command_line: Config) -> Result<(Self, Option<PathBuf>), String> {
+    let file = Self::find_file();
+    let from_file = match &file {
+      Some(path) => {
+        let text = fs::read_to_string(path)
+          .map_err(|e| format!("Can't read {}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("Can't parse {}: {e}", path.display()))?
+      }
+      None => Config::default(),
+    };
+    let from_env = Self::from_env(|var| env::var(var).ok())?;
+    Ok((from_file.merge(from_env).merge(command_line).with_defaults(), file))
+  }
+
+  /// Describe the settings as YAML, which can also be used as a config file.
+  pub fn to_yaml(&self) -> String {
+    serde_yaml::to_string(self).expect("Can't convert config to YAML")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+  use crate::utils::DumpFormat;
+  use crate::utils::config::{Config, Days, Dump};
+
+  #[test]
+  fn test_parse() {
+    let config = Config::parse("input: pavan\nday: 16\ndump_input: json\n").unwrap();
+    assert_eq!(Some("pavan".to_string()), config.input);
+    assert_eq!(Some(Days::Only(16)), config.day);
+    assert_eq!(Some(Dump::Format(DumpFormat::Json)), config.dump_input);
+    let config = Config::parse("day: all\ndump_input: none\n").unwrap();
+    assert_eq!(Some(Days::All), config.day);
+    assert_eq!(Some(Dump::Off), config.dump_input);
+    assert!(Config::parse("day: some\n").unwrap_err().contains("unknown day"));
+    assert_eq!(None, config.seed);
+    assert_eq!(Config::default(), Config::parse("\n").unwrap());
+    assert!(Config::parse("inptu: pavan\n").unwrap_err().contains("unknown field"));
+    let config = Config::parse("params:\n  day1.top: 5\n  day2.name: rps\n").unwrap();
+    assert_eq!(Some("5"), config.params.get("day1.top").map(|s| s.as_str()));
+    assert_eq!(Some("rps"), config.params.get("day2.name").map(|s| s.as_str()));
+    assert!(Config::parse("params:\n  day1.top: [5]\n").is_err());
+  }
+
+  #[test]
+  fn test_precedence() {
+    let file = Config::parse("input: pavan\nday: 16\nseed: 3\n\
+                              params:\n  day1.top: 4\n  day3.x: 1\n").unwrap();
+    let env = Config::from_env(|var| match var {
+      "AOC_DAY" => Some("5".to_string()),
+      "AOC_RENDER_EVERY" => Some("10".to_string()),
+      _ => None,
+    }).unwrap();
+    let cli = Config{day: Some(Days::Only(7)), params: BTreeMap::from([("day1.top".to_string(),
+                                                            "6".to_string())]),
+                     ..Config::default()};
+    let config = file.merge(env).merge(cli).with_defaults();
+    assert_eq!(Config{input: Some("pavan".to_string()), day: Some(Days::Only(7)), render_every: Some(10),
+                      seed: Some(3), params: BTreeMap::from([
+                        ("day1.top".to_string(), "6".to_string()),
+                        ("day3.x".to_string(), "1".to_string())]),
+                      ..Config::default()}, config);
+    assert_eq!("input: pavan\nday: 7\nrender_every: 10\nseed: 3\n\
+                params:\n  day1.top: '6'\n  day3.x: '1'\n", config.to_yaml());
+    // the environment can turn the file's settings back off
+    let file = Config::parse("day: 16\ndump_input: json\n").unwrap();
+    let env = Config::from_env(|var| match var {
+      "AOC_DAY" => Some("all".to_string()),
+      "AOC_DUMP_INPUT" => Some("none".to_string()),
+      _ => None,
+    }).unwrap();
+    let config = file.merge(env);
+    assert_eq!((Some(Days::All), Some(Dump::Off)), (config.day, config.dump_input));
+    assert_eq!("day: all\ndump_input: none\n", config.to_yaml());
+    let file = Config::parse("html: run.html\njunit: run.xml\nrender: frames\n").unwrap();
+    let cli = Config{html: Some("none".to_string()), render: Some("none".to_string()),
+                     ..Config::default()};
+    let config = file.merge(cli).with_defaults();
+    assert_eq!((None, Some("run.xml"), None),
+               (config.html.as_deref(), config.junit.as_deref(), config.render.as_deref()));
+    assert_eq!(Err("Can't parse AOC_SEED='many'".to_string()),
+               Config::from_env(|var| (var == "AOC_SEED").then(|| "many".to_string())));
+  }
+}
@@ -0,0 +1,99 @@
+//! Find when a simulation starts repeating, so that a measurement can be
+//! extrapolated far past the number of steps that are practical to run.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Add, Mul, Sub};
+
+/// Where the repeating part of the sequence is.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub struct Cycle {
+  /// The first step of the first repetition.
+  pub start: usize,
+  /// The number of steps in each repetition.
+  pub length: usize,
+}
+
+/// The measurements from each step of a simulation and the cycle, if one was
+/// found.
+#[derive(Clone,Debug)]
+pub struct History<M> {
+  /// The measurement after each step, starting with the initial state.
+  values: Vec<M>,
+  pub cycle: Option<Cycle>,
+}
+
+impl<M> History<M> where M: Copy + Add<Output=M> + Sub<Output=M> + Mul<Output=M>
+                              + TryFrom<usize> {
+  /// How much does the measurement change over each cycle?
+  pub fn delta(&self) -> Option<M> {
+    let cycle = self.cycle?;
+    Some(self.values[cycle.start + cycle.length] - self.values[cycle.start])
+  }
+
+  /// What is the measurement after the given number of steps? This is None
+  /// if the step is past what was simulated and there wasn't a cycle.
+  pub fn extrapolate(&self, step: usize) -> Option<M> {
+    if step < self.values.len() {
+      return Some(self.values[step])
+    }
+    let cycle = self.cycle?;
+    let cycles = (step - cycle.start) / cycle.length;
+    let offset = (step - cycle.start) % cycle.length;
+    let cycles = M::try_from(cycles).ok().expect("too many cycles for the measurement type");
+    Some(self.values[cycle.start + offset] + self.delta()? * cycles)
+  }
+}
+
+/// Run the simulation until a state repeats or `limit` steps are done. The
+/// key must identify everything about the state that affects the following
+/// steps, but not the measurement that is growing, such as the height.
+pub fn find<S, K, M, KeyFn, StepFn, MeasureFn>(mut state: S, mut key: KeyFn, mut step: StepFn,
+                                             mut measure: MeasureFn, limit: usize) -> History<M>
+  where K: Eq + Hash,
+        KeyFn: FnMut(&S) -> K,
+        StepFn: FnMut(&mut S),
+        MeasureFn: FnMut(&S) -> M {
+  let mut seen: HashMap<K, usize> = HashMap::new();
+  let mut values = vec![measure(&state)];
+  seen.insert(key(&state), 0);
+  for count in 1..=limit {
+    step(&mut state);
+    values.push(measure(&state));
+    if let Some(previous) = seen.insert(key(&state), count) {
+      return History{values, cycle: Some(Cycle{start: previous, length: count - previous})}
+    }
+  }
+  History{values, cycle: None}
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::cycle::{find, Cycle};
+
+  #[test]
+  fn test_cycle() {
+    // x goes 0, 1, 2, 3, 4, 2, 3, 4, ... while the total keeps growing
+    let history = find((0u32, 0u64), |s| s.0,
+                       |s| { s.0 = if s.0 == 4 { 2 } else { s.0 + 1 }; s.1 += s.0 as u64; },
+                       |s| s.1, 1000);
+    assert_eq!(Some(Cycle{start: 2, length: 3}), history.cycle);
+    assert_eq!(Some(9), history.delta());
+    assert_eq!(Some(10), history.extrapolate(4));
+    let mut total = 0;
+    let mut x = 0;
+    for _ in 0..100 {
+      x = if x == 4 { 2 } else { x + 1 };
+      total += x;
+    }
+    assert_eq!(Some(total), history.extrapolate(100));
+  }
+
+  #[test]
+  fn test_no_cycle() {
+    let history = find(0usize, |&s| s, |s| *s += 1, |&s| s * 2, 10);
+    assert_eq!(None, history.cycle);
+    assert_eq!(Some(20), history.extrapolate(10));
+    assert_eq!(None, history.extrapolate(11));
+  }
+}
@@ -0,0 +1,358 @@
+//! Points and directions for the puzzles that move around a plane or a volume.
+//!
+//! The y axis points down, like the rows of the puzzle inputs, so North is
+//! toward smaller y and turning right goes clockwise on the screen.
+
+use serde::Serialize;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// The signed integer types that the distances and directions work with.
+pub trait Coordinate: Copy + Ord + From<i8> + Add<Output=Self> + Sub<Output=Self>
+  + Mul<Output=Self> + Div<Output=Self> + Neg<Output=Self> {
+
+  fn abs(self) -> Self {
+    if self < Self::from(0) { -self } else { self }
+  }
+
+  fn signum(self) -> Self {
+    Self::from(match self.cmp(&Self::from(0)) {
+      std::cmp::Ordering::Less => -1,
+      std::cmp::Ordering::Equal => 0,
+      std::cmp::Ordering::Greater => 1,
+    })
+  }
+}
+
+impl<T> Coordinate for T where T: Copy + Ord + From<i8> + Add<Output=T> + Sub<Output=T>
+  + Mul<Output=T> + Div<Output=T> + Neg<Output=T> {}
+
+/// A point in the plane.
+#[derive(Clone,Copy,Debug,Default,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub struct Point2<T> {
+  pub x: T,
+  pub y: T,
+}
+
+impl<T> Point2<T> {
+  pub const fn new(x: T, y: T) -> Self {
+    Point2{x, y}
+  }
+}
+
+impl<T: Coordinate> Point2<T> {
+  pub fn manhattan(&self, other: &Self) -> T {
+    (self.x - other.x).abs() + (self.y - other.y).abs()
+  }
+
+  pub fn chebyshev(&self, other: &Self) -> T {
+    (self.x - other.x).abs().max((self.y - other.y).abs())
+  }
+
+  /// Reduce each coordinate to -1, 0, or 1.
+  pub fn signum(&self) -> Self {
+    Point2{x: self.x.signum(), y: self.y.signum()}
+  }
+
+  /// Rotate 90 degrees counterclockwise (as drawn) around the origin.
+  pub fn rotate_left(&self) -> Self {
+    Point2{x: self.y, y: -self.x}
+  }
+
+  /// Rotate 90 degrees clockwise (as drawn) around the origin.
+  pub fn rotate_right(&self) -> Self {
+    Point2{x: -self.y, y: self.x}
+  }
+
+  /// Rotate 45 degrees and scale by sqrt(2), so that the diamonds of points
+  /// within a Manhattan distance become axis-aligned squares.
+  pub fn rotate45(&self) -> Self {
+    Point2{x: self.x + self.y, y: self.y - self.x}
+  }
+
+  /// Undo `rotate45`. Half of the rotated points fall between the original
+  /// grid points and give None.
+  pub fn unrotate45(&self) -> Option<Self> {
+    let two = T::from(2);
+    if (self.x - self.y) / two * two != self.x - self.y {
+      return None
+    }
+    Some(Point2{x: (self.x - self.y) / two, y: (self.x + self.y) / two})
+  }
+}
+
+impl<T: Add<Output=T>> Add for Point2<T> {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Point2{x: self.x + other.x, y: self.y + other.y}
+  }
+}
+
+impl<T: AddAssign> AddAssign for Point2<T> {
+  fn add_assign(&mut self, other: Self) {
+    self.x += other.x;
+    self.y += other.y;
+  }
+}
+
+impl<T: Sub<Output=T>> Sub for Point2<T> {
+  type Output = Self;
+
+  fn sub(self, other: Self) -> Self {
+    Point2{x: self.x - other.x, y: self.y - other.y}
+  }
+}
+
+impl<T: SubAssign> SubAssign for Point2<T> {
+  fn sub_assign(&mut self, other: Self) {
+    self.x -= other.x;
+    self.y -= other.y;
+  }
+}
+
+impl<T: Neg<Output=T>> Neg for Point2<T> {
+  type Output = Self;
+
+  fn neg(self) -> Self {
+    Point2{x: -self.x, y: -self.y}
+  }
+}
+
+/// Scale the point by a constant.
+impl<T: Copy + Mul<Output=T>> Mul<T> for Point2<T> {
+  type Output = Self;
+
+  fn mul(self, scale: T) -> Self {
+    Point2{x: self.x * scale, y: self.y * scale}
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "({}, {})", self.x, self.y)
+  }
+}
+
+/// A point in space.
+#[derive(Clone,Copy,Debug,Default,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub struct Point3<T> {
+  pub x: T,
+  pub y: T,
+  pub z: T,
+}
+
+impl<T> Point3<T> {
+  pub const fn new(x: T, y: T, z: T) -> Self {
+    Point3{x, y, z}
+  }
+}
+
+impl<T: Coordinate> Point3<T> {
+  pub fn manhattan(&self, other: &Self) -> T {
+    (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+  }
+
+  pub fn chebyshev(&self, other: &Self) -> T {
+    (self.x - other.x).abs().max((self.y - other.y).abs()).max((self.z - other.z).abs())
+  }
+
+  /// The six points that share a face with this one.
+  pub fn neighbors6(&self) -> [Self; 6] {
+    let (one, zero) = (T::from(1), T::from(0));
+    [Point3::new(one, zero, zero), Point3::new(-one, zero, zero),
+     Point3::new(zero, one, zero), Point3::new(zero, -one, zero),
+     Point3::new(zero, zero, one), Point3::new(zero, zero, -one)]
+      .map(|delta| *self + delta)
+  }
+}
+
+impl<T: Add<Output=T>> Add for Point3<T> {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Point3{x: self.x + other.x, y: self.y + other.y, z: self.z + other.z}
+  }
+}
+
+impl<T: Sub<Output=T>> Sub for Point3<T> {
+  type Output = Self;
+
+  fn sub(self, other: Self) -> Self {
+    Point3{x: self.x - other.x, y: self.y - other.y, z: self.z - other.z}
+  }
+}
+
+impl<T: Neg<Output=T>> Neg for Point3<T> {
+  type Output = Self;
+
+  fn neg(self) -> Self {
+    Point3{x: -self.x, y: -self.y, z: -self.z}
+  }
+}
+
+impl<T: Copy + Mul<Output=T>> Mul<T> for Point3<T> {
+  type Output = Self;
+
+  fn mul(self, scale: T) -> Self {
+    Point3{x: self.x * scale, y: self.y * scale, z: self.z * scale}
+  }
+}
+
+/// The four orthogonal directions in clockwise order.
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub enum Dir4 {
+  North,
+  East,
+  South,
+  West,
+}
+
+impl Dir4 {
+  pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+  /// Turn 90 degrees counterclockwise.
+  pub fn left(self) -> Self {
+    Self::ALL[(self as usize + 3) % 4]
+  }
+
+  /// Turn 90 degrees clockwise.
+  pub fn right(self) -> Self {
+    Self::ALL[(self as usize + 1) % 4]
+  }
+
+  /// Turn around.
+  pub fn flip(self) -> Self {
+    Self::ALL[(self as usize + 2) % 4]
+  }
+
+  /// The change in (x, y) from moving one step in this direction.
+  pub const fn offset(self) -> (isize, isize) {
+    match self {
+      Dir4::North => (0, -1),
+      Dir4::East => (1, 0),
+      Dir4::South => (0, 1),
+      Dir4::West => (-1, 0),
+    }
+  }
+
+  /// The change in position from moving one step in this direction.
+  pub fn delta<T: From<i8>>(self) -> Point2<T> {
+    let (x, y) = self.offset();
+    Point2{x: T::from(x as i8), y: T::from(y as i8)}
+  }
+}
+
+/// The eight compass directions in clockwise order.
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd,Serialize)]
+pub enum Dir8 {
+  North,
+  NorthEast,
+  East,
+  SouthEast,
+  South,
+  SouthWest,
+  West,
+  NorthWest,
+}
+
+impl Dir8 {
+  pub const ALL: [Dir8; 8] = [Dir8::North, Dir8::NorthEast, Dir8::East, Dir8::SouthEast,
+    Dir8::South, Dir8::SouthWest, Dir8::West, Dir8::NorthWest];
+
+  /// Turn 45 degrees counterclockwise.
+  pub fn left(self) -> Self {
+    Self::ALL[(self as usize + 7) % 8]
+  }
+
+  /// Turn 45 degrees clockwise.
+  pub fn right(self) -> Self {
+    Self::ALL[(self as usize + 1) % 8]
+  }
+
+  /// Turn around.
+  pub fn flip(self) -> Self {
+    Self::ALL[(self as usize + 4) % 8]
+  }
+
+  /// The change in (x, y) from moving one step in this direction.
+  pub const fn offset(self) -> (isize, isize) {
+    match self {
+      Dir8::North => (0, -1),
+      Dir8::NorthEast => (1, -1),
+      Dir8::East => (1, 0),
+      Dir8::SouthEast => (1, 1),
+      Dir8::South => (0, 1),
+      Dir8::SouthWest => (-1, 1),
+      Dir8::West => (-1, 0),
+      Dir8::NorthWest => (-1, -1),
+    }
+  }
+
+  /// The change in position from moving one step in this direction.
+  pub fn delta<T: From<i8>>(self) -> Point2<T> {
+    let (x, y) = self.offset();
+    Point2{x: T::from(x as i8), y: T::from(y as i8)}
+  }
+}
+
+impl From<Dir4> for Dir8 {
+  fn from(dir: Dir4) -> Self {
+    Dir8::ALL[dir as usize * 2]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::geom::{Dir4, Dir8, Point2, Point3};
+
+  #[test]
+  fn test_point2() {
+    let a = Point2::new(3, -4);
+    let b = Point2::new(-1, 2);
+    assert_eq!(Point2::new(2, -2), a + b);
+    assert_eq!(Point2::new(4, -6), a - b);
+    assert_eq!(Point2::new(-6, 8), -a * 2);
+    assert_eq!(10, a.manhattan(&b));
+    assert_eq!(6, a.chebyshev(&b));
+    assert_eq!(Point2::new(1, -1), a.signum());
+    assert_eq!(Point2::new(-4, -3), a.rotate_left());
+    assert_eq!(a, a.rotate_left().rotate_right());
+    assert_eq!("(3, -4)", a.to_string());
+  }
+
+  #[test]
+  fn test_rotate45() {
+    let p: Point2<i64> = Point2::new(14, 11);
+    assert_eq!(Point2::new(25, -3), p.rotate45());
+    assert_eq!(Some(p), p.rotate45().unrotate45());
+    assert_eq!(None, Point2::new(25, -2).unrotate45());
+    assert_eq!(None, Point2::new(-3, 0).unrotate45());
+  }
+
+  #[test]
+  fn test_point3() {
+    let a = Point3::new(1, 2, 3);
+    assert_eq!(9, a.manhattan(&Point3::new(-1, 0, -2)));
+    assert_eq!(5, a.chebyshev(&Point3::new(-1, 0, -2)));
+    let neighbors = a.neighbors6();
+    assert!(neighbors.iter().all(|n| n.manhattan(&a) == 1));
+    assert_eq!(a, neighbors[0] - Point3::new(1, 0, 0));
+  }
+
+  #[test]
+  fn test_directions() {
+    assert_eq!(Dir4::West, Dir4::North.left());
+    assert_eq!(Dir4::East, Dir4::North.right());
+    assert_eq!(Dir4::South, Dir4::North.flip());
+    assert_eq!(Point2::new(0i32, -1), Dir4::North.delta());
+    assert_eq!(Point2::new(1i32, 0), Dir4::North.delta::<i32>().rotate_right());
+    assert_eq!(Dir8::NorthWest, Dir8::North.left());
+    assert_eq!(Dir8::SouthWest, Dir8::NorthEast.flip());
+    assert_eq!(Dir8::West, Dir8::from(Dir4::West));
+    for dir in Dir4::ALL {
+      assert_eq!(Dir8::from(dir).delta::<i16>(), dir.delta());
+      assert_eq!(dir.right().delta::<i16>(), dir.delta::<i16>().rotate_right());
+    }
+  }
+}
@@ -0,0 +1,265 @@
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeSeq;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::utils::geom::{Dir4, Dir8};
+
+/// A position in a grid as (x, y), where y counts down from the top row.
+pub type Position = (usize, usize);
+
+/// The offsets to the four orthogonal neighbors.
+pub const NEIGHBORS4: [(isize, isize); 4] =
+  [Dir4::North.offset(), Dir4::East.offset(), Dir4::South.offset(), Dir4::West.offset()];
+
+/// The offsets to the eight neighbors, including the diagonals.
+pub const NEIGHBORS8: [(isize, isize); 8] =
+  [Dir8::North.offset(), Dir8::NorthEast.offset(), Dir8::East.offset(),
+   Dir8::SouthEast.offset(), Dir8::South.offset(), Dir8::SouthWest.offset(),
+   Dir8::West.offset(), Dir8::NorthWest.offset()];
+
+/// A dense, rectangular 2D grid stored in row-major order.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+  /// Create a grid with every cell set to the given value.
+  pub fn new(width: usize, height: usize, fill: T) -> Self {
+    Grid{width, height, cells: vec![fill; width * height]}
+  }
+
+  /// Set every cell to the given value.
+  pub fn fill(&mut self, value: T) {
+    self.cells.fill(value);
+  }
+}
+
+impl<T> Grid<T> {
+  /// Build a grid by calling the function for each position in row-major order.
+  pub fn from_fn<F: FnMut(Position) -> T>(width: usize, height: usize, mut build: F) -> Self {
+    let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(&mut build).collect();
+    Grid{width, height, cells}
+  }
+
+  /// Build a grid from a list of rows, padding short rows with `fill`.
+  pub fn from_rows(rows: Vec<Vec<T>>, fill: impl Fn() -> T) -> Self {
+    let height = rows.len();
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut cells = Vec::with_capacity(width * height);
+    for row in rows {
+      let padding = width - row.len();
+      cells.extend(row);
+      cells.extend((0..padding).map(|_| fill()));
+    }
+    Grid{width, height, cells}
+  }
+
+  /// Parse a character map, converting each character with the given function,
+  /// which also gets the character's position. Short rows are padded as if they
+  /// ended in spaces.
+  pub fn parse_with<F: FnMut(Position, char) -> T>(input: &str, mut convert: F) -> Self {
+    let lines: Vec<&str> = input.lines().collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut cells = Vec::with_capacity(width * lines.len());
+    for (y, line) in lines.iter().enumerate() {
+      let mut chars = line.chars();
+      for x in 0..width {
+        cells.push(convert((x, y), chars.next().unwrap_or(' ')));
+      }
+    }
+    Grid{width, height: lines.len(), cells}
+  }
+
+  /// Parse a character map, converting each character with the given function.
+  pub fn parse<F: FnMut(char) -> T>(input: &str, mut convert: F) -> Self {
+    Self::parse_with(input, |_, ch| convert(ch))
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  /// Convert signed or unsigned coordinates into a position if they are
+  /// inside of the grid.
+  pub fn position<C: TryInto<usize>>(&self, x: C, y: C) -> Option<Position> {
+    let x = x.try_into().ok()?;
+    let y = y.try_into().ok()?;
+    if x < self.width && y < self.height { Some((x, y)) } else { None }
+  }
+
+  /// Get the cell at the given coordinates or None if it is outside the grid.
+  pub fn get<C: TryInto<usize>>(&self, x: C, y: C) -> Option<&T> {
+    let (x, y) = self.position(x, y)?;
+    Some(&self.cells[y * self.width + x])
+  }
+
+  /// Get a mutable cell at the given coordinates or None if it is outside the grid.
+  pub fn get_mut<C: TryInto<usize>>(&mut self, x: C, y: C) -> Option<&mut T> {
+    let (x, y) = self.position(x, y)?;
+    Some(&mut self.cells[y * self.width + x])
+  }
+
+  /// Move from the position by the given offset, if the result is in the grid.
+  pub fn offset(&self, pos: Position, delta: (isize, isize)) -> Option<Position> {
+    self.position(pos.0.checked_add_signed(delta.0)?, pos.1.checked_add_signed(delta.1)?)
+  }
+
+  /// Iterate through the orthogonal neighbors that are in the grid.
+  pub fn neighbors4(&self, pos: Position) -> impl Iterator<Item=Position> + '_ {
+    NEIGHBORS4.iter().filter_map(move |&d| self.offset(pos, d))
+  }
+
+  /// Iterate through the orthogonal and diagonal neighbors that are in the grid.
+  pub fn neighbors8(&self, pos: Position) -> impl Iterator<Item=Position> + '_ {
+    NEIGHBORS8.iter().filter_map(move |&d| self.offset(pos, d))
+  }
+
+  /// Iterate through the positions starting next to `pos` and repeatedly
+  /// moving by `delta` until leaving the grid.
+  pub fn ray(&self, pos: Position, delta: (isize, isize)) -> impl Iterator<Item=Position> + '_ {
+    let mut current = Some(pos);
+    std::iter::from_fn(move || {
+      current = self.offset(current?, delta);
+      current
+    })
+  }
+
+  /// Get the given row.
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  /// Iterate through the rows from top to bottom.
+  pub fn rows(&self) -> impl DoubleEndedIterator<Item=&[T]> {
+    // chunks panics on a zero width, so make sure there is at least 1
+    self.cells.chunks(self.width.max(1))
+  }
+
+  /// Iterate down the given column.
+  pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item=&T> {
+    assert!(x < self.width, "column {x} is past the width of {}", self.width);
+    self.cells[x..].iter().step_by(self.width)
+  }
+
+  /// Iterate through the cells on the down-right diagonal that starts at `pos`.
+  pub fn diagonal(&self, pos: Position) -> impl Iterator<Item=&T> {
+    std::iter::once(pos).chain(self.ray(pos, (1, 1))).map(move |p| &self[p])
+  }
+
+  /// Iterate through the cells on the down-left diagonal that starts at `pos`.
+  pub fn anti_diagonal(&self, pos: Position) -> impl Iterator<Item=&T> {
+    std::iter::once(pos).chain(self.ray(pos, (-1, 1))).map(move |p| &self[p])
+  }
+
+  /// Iterate through all of the positions in row-major order.
+  pub fn positions(&self) -> impl Iterator<Item=Position> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+  }
+
+  /// Iterate through all of the cells with their positions.
+  pub fn iter(&self) -> impl Iterator<Item=(Position, &T)> {
+    self.positions().zip(self.cells.iter())
+  }
+
+  /// Build a new grid by converting each cell.
+  pub fn map<U, F: FnMut(&T) -> U>(&self, convert: F) -> Grid<U> {
+    Grid{width: self.width, height: self.height, cells: self.cells.iter().map(convert).collect()}
+  }
+}
+
+impl<T> Index<Position> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, (x, y): Position) -> &T {
+    assert!(x < self.width, "x {x} is outside of width {}", self.width);
+    &self.cells[y * self.width + x]
+  }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+  fn index_mut(&mut self, (x, y): Position) -> &mut T {
+    assert!(x < self.width, "x {x} is outside of width {}", self.width);
+    &mut self.cells[y * self.width + x]
+  }
+}
+
+/// Display each cell with its own formatting and each row on its own line.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for row in self.rows() {
+      for cell in row {
+        write!(f, "{cell}")?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+/// Serialize the grid as a list of rows.
+impl<T: Serialize> Serialize for Grid<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(self.height))?;
+    for row in self.rows() {
+      seq.serialize_element(row)?;
+    }
+    seq.end()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::grid::Grid;
+
+  #[test]
+  fn test_parse() {
+    let grid = Grid::parse("#..\n.#\n", |ch| ch == '#');
+    assert_eq!((3, 2), (grid.width(), grid.height()));
+    assert_eq!(Some(&true), grid.get(1, 1));
+    assert_eq!(Some(&false), grid.get(2, 1));
+    assert_eq!(None, grid.get(-1, 1));
+    assert_eq!(None, grid.get(3usize, 0));
+    assert_eq!("#..\n.#.\n", grid.map(|&b| if b { '#' } else { '.' }).to_string());
+  }
+
+  #[test]
+  fn test_neighbors() {
+    let grid = Grid::new(3, 3, 0);
+    assert_eq!(vec![(1, 0), (0, 1)], grid.neighbors4((0, 0)).collect::<Vec<_>>());
+    assert_eq!(4, grid.neighbors4((1, 1)).count());
+    assert_eq!(8, grid.neighbors8((1, 1)).count());
+    assert_eq!(3, grid.neighbors8((2, 2)).count());
+    let grid = Grid::from_fn(3, 2, |(x, y)| x + 10 * y);
+    assert_eq!(&[10, 11, 12], grid.row(1));
+  }
+
+  #[test]
+  fn test_lines() {
+    let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8]], || 0);
+    assert_eq!(&[7, 8, 0], grid.row(2));
+    assert_eq!(vec![2, 5, 8], grid.column(1).cloned().collect::<Vec<_>>());
+    assert_eq!(vec![0, 6, 3], grid.column(2).rev().cloned().collect::<Vec<_>>());
+    assert_eq!(vec![1, 5, 0], grid.diagonal((0, 0)).cloned().collect::<Vec<_>>());
+    assert_eq!(vec![3, 5, 7], grid.anti_diagonal((2, 0)).cloned().collect::<Vec<_>>());
+    assert_eq!(vec![(1, 2), (0, 2)], grid.ray((2, 2), (-1, 0)).collect::<Vec<_>>());
+    assert_eq!(45, grid.iter().map(|(_, v)| v).sum::<i32>() + 9);
+    assert_eq!("- - 1\n  - 2\n  - 3\n", serde_yaml::to_string(
+      &Grid::from_rows(vec![vec![1, 2, 3]], || 0)).unwrap());
+  }
+
+  #[test]
+  #[should_panic(expected = "column 3 is past the width of 3")]
+  fn test_column_past_width() {
+    Grid::new(3, 2, 0).column(3).count();
+  }
+}
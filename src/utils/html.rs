@@ -0,0 +1,175 @@
+use std::fmt::Write as FmtWrite;
+use std::{fs, io, time};
+
+use crate::utils::{Answer, DayResult};
+use crate::utils::escape_xml as escape;
+use crate::utils::answers::{Answers, Check};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+th { background: #eee; cursor: pointer; }
+td.time { text-align: right; }
+pre { margin: 0; font-family: monospace; }
+.match { color: #080; }
+.mismatch { color: #c00; font-weight: bold; }
+.missing { color: #888; }
+";
+
+/// Sort the table by the clicked column, toggling the direction on each click.
+/// Cells with a data-sort attribute are compared numerically.
+const SCRIPT: &str = "
+document.querySelectorAll('th').forEach((th, col) => th.addEventListener('click', () => {
+  const body = th.closest('table').tBodies[0];
+  const dir = th.dataset.dir = th.dataset.dir === 'asc' ? 'desc' : 'asc';
+  const key = r => { const c = r.cells[col]; return c.dataset.sort !== undefined ? Number(c.dataset.sort) : c.innerText; };
+  const rows = Array.from(body.rows).sort((a, b) => {
+    const l = key(a), r = key(b);
+    const order = typeof l === 'number' ? l - r : l.localeCompare(r, undefined, {numeric: true});
+    return dir === 'asc' ? order : -order;
+  });
+  rows.forEach(r => body.appendChild(r));
+}));
+";
+
+/// The colors for the generator, part 1, and part 2 bars.
+const PHASE_COLORS: [&str; 3] = ["#999", "#48c", "#e83"];
+const CHART_WIDTH: f64 = 600.0;
+const BAR_HEIGHT: usize = 16;
+const LABEL_WIDTH: usize = 70;
+
+/// Render an answer, using a monospace block for grids.
+fn format_answer(answer: &Answer) -> String {
+  match answer {
+    Answer::Grid(rows) => format!("<pre>{}</pre>",
+                                  rows.iter().map(|row| escape(row)).collect::<Vec<_>>().join("\n")),
+    Answer::None => "<span class=\"missing\">none</span>".to_string(),
+    Answer::Error(message) => format!("<span class=\"mismatch\">{}</span>", escape(message)),
+    _ => escape(&answer.to_string()),
+  }
+}
+
+/// Render the check status as a table cell.
+fn format_check(check: &Check) -> String {
+  match check {
+    Check::Match => "<td class=\"match\">ok</td>".to_string(),
+    Check::Mismatch(expected) =>
+      format!("<td class=\"mismatch\">expected {}</td>", format_answer(expected)),
+    Check::Missing => "<td class=\"missing\">new</td>".to_string(),
+    Check::NoAnswer => "<td class=\"missing\">-</td>".to_string(),
+    Check::Error => "<td class=\"mismatch\">error</td>".to_string(),
+  }
+}
+
+fn table_row(out: &mut String, result: &DayResult, phase: &str, duration: time::Duration,
+             answer: Option<&Answer>, check: Option<Check>) {
+  let day_num = result.day.trim_start_matches("day");
+  write!(out, "<tr><td data-sort=\"{day_num}\">{}</td><td>{phase}</td>\
+               <td class=\"time\" data-sort=\"{}\">{:.2?}</td><td>{}</td>",
+         escape(&result.pretty_day()), duration.as_nanos(), duration,
+         answer.map(format_answer).unwrap_or_default()).unwrap();
+  match check {
+    Some(check) => out.push_str(&format_check(&check)),
+    None => out.push_str("<td></td>"),
+  }
+  out.push_str("</tr>\n");
+}
+
+/// Build a horizontal stacked bar chart of the phase times for each day.
+fn bar_chart(out: &mut String, results: &[DayResult]) {
+  let longest = results.iter()
+    .map(|r| r.generate_time + r.part1.0 + r.part2.0)
+    .max().unwrap_or_default().as_secs_f64();
+  let scale = if longest > 0.0 { CHART_WIDTH / longest } else { 0.0 };
+  let height = (results.len() + 1) * (BAR_HEIGHT + 4);
+  writeln!(out, "<svg width=\"{}\" height=\"{height}\" \
+                 xmlns=\"http://www.w3.org/2000/svg\" font-size=\"12\">",
+           LABEL_WIDTH + CHART_WIDTH as usize + 100).unwrap();
+  for (row, result) in results.iter().enumerate() {
+    let y = row * (BAR_HEIGHT + 4);
+    writeln!(out, "<text x=\"0\" y=\"{}\">{}</text>", y + BAR_HEIGHT - 4,
+             escape(&result.pretty_day())).unwrap();
+    let mut x = LABEL_WIDTH as f64;
+    for (phase, (name, duration)) in [("Generator", result.generate_time),
+        ("Part 1", result.part1.0), ("Part 2", result.part2.0)].iter().enumerate() {
+      let width = duration.as_secs_f64() * scale;
+      writeln!(out, "<rect x=\"{x:.1}\" y=\"{y}\" width=\"{width:.1}\" height=\"{BAR_HEIGHT}\" \
+                     fill=\"{}\"><title>{name}: {duration:.2?}</title></rect>",
+               PHASE_COLORS[phase]).unwrap();
+      x += width;
+    }
+    writeln!(out, "<text x=\"{:.1}\" y=\"{}\">{:.2?}</text>", x + 4.0, y + BAR_HEIGHT - 4,
+             result.generate_time + result.part1.0 + result.part2.0).unwrap();
+  }
+  // the legend goes on the last row
+  let y = results.len() * (BAR_HEIGHT + 4);
+  for (phase, name) in ["Generator", "Part 1", "Part 2"].iter().enumerate() {
+    let x = LABEL_WIDTH + phase * 100;
+    writeln!(out, "<rect x=\"{x}\" y=\"{y}\" width=\"{BAR_HEIGHT}\" height=\"{BAR_HEIGHT}\" \
+                   fill=\"{}\"/><text x=\"{}\" y=\"{}\">{name}</text>",
+             PHASE_COLORS[phase], x + BAR_HEIGHT + 4, y + BAR_HEIGHT - 4).unwrap();
+  }
+  out.push_str("</svg>\n");
+}
+
+/// Build a self-contained HTML page describing the results of a run.
+/// The answers are checked against the previously stored answers.
+pub fn build_report(input: &str, results: &[DayResult], answers: &Answers,
+                    elapsed: time::Duration) -> String {
+  let mut out = String::new();
+  writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>Advent of Code 2022: {}</title>\n<style>{STYLE}</style>\n</head>\n<body>",
+           escape(input)).unwrap();
+  writeln!(out, "<h1>Advent of Code 2022</h1>\n<p>Input: {} &middot; Overall runtime: {:.2?}</p>",
+           escape(input), elapsed).unwrap();
+  out.push_str("<h2>Timings</h2>\n");
+  bar_chart(&mut out, results);
+  out.push_str("<h2>Results</h2>\n<table>\n<thead><tr><th>Day</th><th>Phase</th><th>Time</th>\
+                <th>Answer</th><th>Check</th></tr></thead>\n<tbody>\n");
+  for result in results {
+    table_row(&mut out, result, "Generator", result.generate_time, None, None);
+    for (part, (duration, answer)) in [&result.part1, &result.part2].iter().enumerate() {
+      table_row(&mut out, result, &format!("Part {}", part + 1), *duration, Some(answer),
+                Some(answers.check(&result.day, part, answer)));
+    }
+  }
+  writeln!(out, "</tbody>\n</table>\n<script>{SCRIPT}</script>\n</body>\n</html>").unwrap();
+  out
+}
+
+/// Write the HTML report to the given file.
+pub fn write_report(filename: &str, input: &str, results: &[DayResult], answers: &Answers,
+                    elapsed: time::Duration) -> io::Result<()> {
+  fs::write(filename, build_report(input, results, answers, elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+  use crate::utils::{Answer, DayResult};
+  use crate::utils::answers::Answers;
+  use crate::utils::escape_xml;
+  use crate::utils::html::build_report;
+
+  #[test]
+  fn test_escape() {
+    assert_eq!("&lt;a href=&quot;x&quot;&gt;&amp;", escape_xml("<a href=\"x\">&"));
+    assert_eq!("it&apos;s&#10;done", escape_xml("it's\ndone"));
+  }
+
+  #[test]
+  fn test_report() {
+    let answers: Answers =
+      serde_yaml::from_str("days:\n  day10:\n  - '13140'\n  - 'old'\n").unwrap();
+    let results = vec![DayResult{day: "day10".to_string(),
+      generate_time: Duration::from_micros(5),
+      part1: (Duration::from_micros(10), Answer::Integer(13140)),
+      part2: (Duration::from_micros(20), Answer::grid("#.#\n.#.\n"))}];
+    let html = build_report("input", &results, &answers, Duration::from_micros(40));
+    assert!(html.contains("<pre>#.#\n.#.</pre>"));
+    assert!(html.contains("<td class=\"match\">ok</td>"));
+    assert!(html.contains("<td class=\"mismatch\">expected old</td>"));
+    assert!(html.contains("<svg"));
+  }
+}
@@ -0,0 +1,184 @@
+//! Sets of integers stored as sorted, disjoint, half-open ranges.
+
+use serde::Serialize;
+use std::ops::{Add, Range, Sub};
+
+/// A set of values stored as the ranges that cover it. The ranges are kept
+/// sorted, and ranges that overlap or touch are merged, so each set has a
+/// single representation.
+#[derive(Clone,Debug,Default,Eq,Hash,PartialEq,Serialize)]
+pub struct IntervalSet<T> {
+  ranges: Vec<Range<T>>,
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+  pub fn new() -> Self {
+    IntervalSet{ranges: Vec::new()}
+  }
+
+  /// Build a set from ranges that are already sorted by their start.
+  fn from_sorted(sorted: impl IntoIterator<Item=Range<T>>) -> Self {
+    let mut ranges: Vec<Range<T>> = Vec::new();
+    for range in sorted.into_iter().filter(|r| r.start < r.end) {
+      match ranges.last_mut() {
+        Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+        _ => ranges.push(range),
+      }
+    }
+    IntervalSet{ranges}
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ranges.is_empty()
+  }
+
+  /// The disjoint ranges in increasing order.
+  pub fn ranges(&self) -> &[Range<T>] {
+    &self.ranges
+  }
+
+  /// Add a range to the set.
+  pub fn insert(&mut self, range: Range<T>) {
+    if range.start >= range.end {
+      return
+    }
+    // the ranges that overlap or touch the new one
+    let first = self.ranges.partition_point(|r| r.end < range.start);
+    let last = self.ranges.partition_point(|r| r.start <= range.end);
+    let merged = if first < last {
+      self.ranges[first].start.min(range.start)..self.ranges[last - 1].end.max(range.end)
+    } else {
+      range
+    };
+    self.ranges.splice(first..last, [merged]);
+  }
+
+  /// Is the value in the set?
+  pub fn contains(&self, value: &T) -> bool {
+    let idx = self.ranges.partition_point(|r| r.end <= *value);
+    self.ranges.get(idx).is_some_and(|r| r.start <= *value)
+  }
+
+  /// Is every value of the other set in this one?
+  pub fn is_superset(&self, other: &Self) -> bool {
+    other.difference(self).is_empty()
+  }
+
+  /// The values that are in either set.
+  pub fn union(&self, other: &Self) -> Self {
+    let mut left = self.ranges.iter().peekable();
+    let mut right = other.ranges.iter().peekable();
+    Self::from_sorted(std::iter::from_fn(|| {
+      match (left.peek(), right.peek()) {
+        (Some(l), Some(r)) if l.start <= r.start => left.next(),
+        (Some(_), Some(_)) => right.next(),
+        (Some(_), None) => left.next(),
+        (None, _) => right.next(),
+      }.cloned()
+    }))
+  }
+
+  /// The values that are in both sets.
+  pub fn intersection(&self, other: &Self) -> Self {
+    let mut ranges = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < self.ranges.len() && j < other.ranges.len() {
+      let (l, r) = (&self.ranges[i], &other.ranges[j]);
+      let start = l.start.max(r.start);
+      let end = l.end.min(r.end);
+      if start < end {
+        ranges.push(start..end);
+      }
+      if l.end < r.end { i += 1 } else { j += 1 }
+    }
+    IntervalSet{ranges}
+  }
+
+  /// The values that are in this set, but not the other.
+  pub fn difference(&self, other: &Self) -> Self {
+    let mut ranges = Vec::new();
+    let mut j = 0;
+    for range in &self.ranges {
+      let mut start = range.start;
+      // skip the ranges that end before this one
+      while j < other.ranges.len() && other.ranges[j].end <= start {
+        j += 1;
+      }
+      let mut k = j;
+      while k < other.ranges.len() && other.ranges[k].start < range.end {
+        if start < other.ranges[k].start {
+          ranges.push(start..other.ranges[k].start);
+        }
+        start = start.max(other.ranges[k].end);
+        k += 1;
+      }
+      if start < range.end {
+        ranges.push(start..range.end);
+      }
+    }
+    IntervalSet{ranges}
+  }
+
+  /// Iterate through the ranges between the ranges of the set.
+  pub fn gaps(&self) -> impl Iterator<Item=Range<T>> + '_ {
+    self.ranges.windows(2).map(|pair| pair[0].end..pair[1].start)
+  }
+}
+
+impl<T: Copy + Ord + Default + Add<Output=T> + Sub<Output=T>> IntervalSet<T> {
+  /// The number of values in the set.
+  pub fn len(&self) -> T {
+    self.ranges.iter().fold(T::default(), |sum, r| sum + (r.end - r.start))
+  }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+  fn from(range: Range<T>) -> Self {
+    Self::from_sorted([range])
+  }
+}
+
+/// Sort and merge the ranges in O(n log n).
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+  fn from_iter<I: IntoIterator<Item=Range<T>>>(iter: I) -> Self {
+    let mut ranges: Vec<Range<T>> = iter.into_iter().collect();
+    ranges.sort_unstable_by_key(|r| r.start);
+    Self::from_sorted(ranges)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::intervals::IntervalSet;
+
+  #[test]
+  fn test_build() {
+    let set: IntervalSet<i64> = [5..8, -2..3, 3..4, 10..10, 6..9].into_iter().collect();
+    assert_eq!(&[-2..4, 5..9], set.ranges());
+    assert_eq!(10, set.len());
+    assert!(set.contains(&-2));
+    assert!(set.contains(&3));
+    assert!(!set.contains(&4));
+    assert!(!set.contains(&9));
+    assert_eq!(vec![4..5], set.gaps().collect::<Vec<_>>());
+    let mut set = set;
+    set.insert(20..22);
+    set.insert(0..6);
+    assert_eq!(&[-2..9, 20..22], set.ranges());
+    set.insert(9..20);
+    assert_eq!(IntervalSet::from(-2..22), set);
+  }
+
+  #[test]
+  fn test_operations() {
+    let left: IntervalSet<i32> = [0..5, 10..15, 20..25].into_iter().collect();
+    let right: IntervalSet<i32> = [3..12, 14..21, 30..31].into_iter().collect();
+    assert_eq!(&[0..25, 30..31], left.union(&right).ranges());
+    assert_eq!(&[3..5, 10..12, 14..15, 20..21], left.intersection(&right).ranges());
+    assert_eq!(&[0..3, 12..14, 21..25], left.difference(&right).ranges());
+    assert_eq!(&[5..10, 15..20, 30..31], right.difference(&left).ranges());
+    assert!(left.is_superset(&IntervalSet::from(11..13)));
+    assert!(!left.is_superset(&IntervalSet::from(11..16)));
+    assert!(left.is_superset(&IntervalSet::new()));
+  }
+}
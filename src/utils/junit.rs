@@ -0,0 +1,110 @@
+use std::fmt::Write as FmtWrite;
+use std::{fs, io, time};
+
+use crate::utils::{Answer, DayResult};
+use crate::utils::escape_xml as escape;
+use crate::utils::answers::{Answers, Check};
+
+/// A single test case, which is one part of a day.
+struct TestCase<'a> {
+  day: &'a str,
+  part: usize,
+  duration: time::Duration,
+  answer: &'a Answer,
+  check: Check,
+}
+
+impl TestCase<'_> {
+  fn write(&self, out: &mut String) {
+    write!(out, "    <testcase classname=\"{}\" name=\"part{}\" time=\"{:.6}\"",
+           escape(self.day), self.part + 1, self.duration.as_secs_f64()).unwrap();
+    match &self.check {
+      Check::Match => out.push_str("/>\n"),
+      Check::Mismatch(expected) => {
+        let (expected, actual) = (expected.to_string(), self.answer.to_string());
+        let message = format!("expected {:?}, got {:?}", expected, actual);
+        writeln!(out, ">\n      <failure message=\"{}\" type=\"WrongAnswer\">\
+                       expected: {}&#10;actual: {}</failure>\n    </testcase>",
+                 escape(&message), escape(&expected), escape(&actual)).unwrap();
+      },
+      Check::Missing =>
+        out.push_str(">\n      <skipped message=\"no stored answer\"/>\n    </testcase>\n"),
+      Check::Error => writeln!(out, ">\n      <error message=\"{}\" type=\"SolverError\"/>\n    \
+                                    </testcase>", escape(&self.answer.to_string())).unwrap(),
+      Check::NoAnswer =>
+        out.push_str(">\n      <skipped message=\"no answer for this part\"/>\n    </testcase>\n"),
+    }
+  }
+}
+
+/// Build a JUnit XML document with a test case for each part of each day.
+/// A case passes if it matches the stored answer, fails if it differs, is an
+/// error if the solver failed, and is skipped if there isn't a stored answer
+/// or the part has no answer.
+pub fn build_report(input: &str, results: &[DayResult], answers: &Answers,
+                    elapsed: time::Duration) -> String {
+  let cases: Vec<TestCase> = results.iter()
+    .flat_map(|r| [&r.part1, &r.part2].into_iter().enumerate()
+      .map(|(part, (duration, answer))| TestCase{day: &r.day, part, duration: *duration,
+        answer, check: answers.check(&r.day, part, answer)}))
+    .collect();
+  let failures = cases.iter().filter(|c| matches!(c.check, Check::Mismatch(_))).count();
+  let errors = cases.iter().filter(|c| c.check == Check::Error).count();
+  let skipped = cases.iter()
+    .filter(|c| matches!(c.check, Check::Missing | Check::NoAnswer)).count();
+  let mut out = String::new();
+  out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  writeln!(out, "<testsuites name=\"aoc2022\" tests=\"{}\" failures=\"{failures}\" \
+                 errors=\"{errors}\" skipped=\"{skipped}\" time=\"{:.6}\">",
+           cases.len(), elapsed.as_secs_f64()).unwrap();
+  writeln!(out, "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" \
+                 errors=\"{errors}\" skipped=\"{skipped}\" time=\"{:.6}\">",
+           escape(input), cases.len(), elapsed.as_secs_f64()).unwrap();
+  for case in &cases {
+    case.write(&mut out);
+  }
+  out.push_str("  </testsuite>\n</testsuites>\n");
+  out
+}
+
+/// Write the JUnit XML report to the given file.
+pub fn write_report(filename: &str, input: &str, results: &[DayResult], answers: &Answers,
+                    elapsed: time::Duration) -> io::Result<()> {
+  fs::write(filename, build_report(input, results, answers, elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+  use crate::utils::{Answer, DayResult};
+  use crate::utils::answers::Answers;
+  use crate::utils::junit::build_report;
+
+  #[test]
+  fn test_report() {
+    let answers: Answers =
+      serde_yaml::from_str("days:\n  day1:\n  - '24000'\n  - '45001'\n").unwrap();
+    let results = vec![
+      DayResult{day: "day1".to_string(), generate_time: Duration::from_micros(5),
+        part1: (Duration::from_millis(10), Answer::Integer(24000)),
+        part2: (Duration::from_millis(20), Answer::Integer(45000))},
+      DayResult{day: "day2".to_string(), generate_time: Duration::from_micros(5),
+        part1: (Duration::from_millis(1), Answer::Integer(15)),
+        part2: (Duration::from_millis(2), Answer::None)},
+      DayResult{day: "day3".to_string(), generate_time: Duration::from_micros(5),
+        part1: (Duration::from_millis(1),
+                Answer::Error("day3 part 1: u64 overflow in sum (a + b)".to_string())),
+        part2: (Duration::from_millis(2), Answer::None)}];
+    let xml = build_report("input", &results, &answers, Duration::from_millis(40));
+    assert!(xml.contains("tests=\"6\" failures=\"1\" errors=\"1\" skipped=\"3\""));
+    assert!(xml.contains("<testcase classname=\"day1\" name=\"part1\" time=\"0.010000\"/>"));
+    assert!(xml.contains("<failure message=\"expected &quot;45001&quot;, \
+                          got &quot;45000&quot;\" type=\"WrongAnswer\">"));
+    assert!(xml.contains("<testcase classname=\"day2\" name=\"part2\" time=\"0.002000\">\n      \
+                          <skipped message=\"no answer for this part\"/>"));
+    assert!(xml.contains("<testcase classname=\"day2\" name=\"part1\" time=\"0.001000\">\n      \
+                          <skipped message=\"no stored answer\"/>"));
+    assert!(xml.contains("<error message=\"day3 part 1: u64 overflow in sum (a + b)\" \
+                          type=\"SolverError\"/>"));
+  }
+}
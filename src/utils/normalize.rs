@@ -0,0 +1,128 @@
+use std::fmt;
+
+/// A change that normalizing made to an input.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Change {
+  /// Removed the UTF-8 byte order mark from the start.
+  RemovedBom,
+  /// Converted the given number of CRLF line endings to LF.
+  ConvertedLineEndings(usize),
+  /// Removed trailing spaces or tabs from the given number of lines.
+  TrimmedTrailingWhitespace(usize),
+  /// Removed the given number of blank lines from the end.
+  RemovedTrailingBlankLines(usize),
+}
+
+impl Change {
+  /// Is the change worth telling the user about? Trailing whitespace is
+  /// common in inputs, including the puzzle's own, so trimming it isn't.
+  pub fn is_notable(&self) -> bool {
+    !matches!(self, Change::TrimmedTrailingWhitespace(_))
+  }
+}
+
+/// The plural suffix for a count.
+fn plural(n: usize) -> &'static str {
+  if n == 1 { "" } else { "s" }
+}
+
+impl fmt::Display for Change {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Change::RemovedBom => write!(f, "removed the UTF-8 byte order mark"),
+      Change::ConvertedLineEndings(n) =>
+        write!(f, "converted {n} CRLF line ending{}", plural(*n)),
+      Change::TrimmedTrailingWhitespace(n) =>
+        write!(f, "trimmed trailing whitespace from {n} line{}", plural(*n)),
+      Change::RemovedTrailingBlankLines(n) =>
+        write!(f, "removed {n} trailing blank line{}", plural(*n)),
+    }
+  }
+}
+
+/// An input after normalization and the list of what was changed.
+#[derive(Debug)]
+pub struct Normalized {
+  pub text: String,
+  pub changes: Vec<Change>,
+}
+
+/// Clean up the formatting differences that editors and operating systems
+/// introduce, so that the generators can split on "\n\n" and use fixed
+/// offsets. Leading whitespace is significant (day5's stacks and day22's map)
+/// and is left alone. If the input ended with a newline, it still does.
+pub fn normalize(input: &str) -> Normalized {
+  let mut changes = Vec::new();
+  let mut text = input;
+  if let Some(rest) = text.strip_prefix('\u{feff}') {
+    changes.push(Change::RemovedBom);
+    text = rest;
+  }
+  let mut crlf = 0;
+  let mut trimmed = 0;
+  let mut lines: Vec<&str> = text.split('\n')
+    .map(|line| {
+      let line = match line.strip_suffix('\r') {
+        Some(l) => { crlf += 1; l },
+        None => line,
+      };
+      let trimmed_line = line.trim_end_matches([' ', '\t']);
+      if trimmed_line.len() != line.len() {
+        trimmed += 1;
+      }
+      trimmed_line
+    })
+    .collect();
+  // split leaves an empty string after a final newline
+  let final_newline = lines.len() > 1 && lines.last() == Some(&"");
+  if final_newline {
+    lines.pop();
+  }
+  let mut blank = 0;
+  while lines.len() > 1 && lines.last() == Some(&"") {
+    lines.pop();
+    blank += 1;
+  }
+  if crlf > 0 {
+    changes.push(Change::ConvertedLineEndings(crlf));
+  }
+  if trimmed > 0 {
+    changes.push(Change::TrimmedTrailingWhitespace(trimmed));
+  }
+  if blank > 0 {
+    changes.push(Change::RemovedTrailingBlankLines(blank));
+  }
+  let mut text = lines.join("\n");
+  if final_newline || blank > 0 {
+    text.push('\n');
+  }
+  Normalized{text, changes}
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::normalize::{Change, normalize};
+
+  #[test]
+  fn test_unchanged() {
+    let input = "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 2 to 1\n";
+    let result = normalize(input);
+    assert_eq!(input, result.text);
+    assert!(result.changes.is_empty());
+    assert_eq!("abc", normalize("abc").text);
+  }
+
+  #[test]
+  fn test_normalize() {
+    let result = normalize("\u{feff}1000\r\n2000 \r\n\r\n        ...#\t\r\n\r\n \r\n");
+    assert_eq!("1000\n2000\n\n        ...#\n", result.text);
+    assert_eq!(vec![Change::RemovedBom, Change::ConvertedLineEndings(6),
+                    Change::TrimmedTrailingWhitespace(3),
+                    Change::RemovedTrailingBlankLines(2)], result.changes);
+    assert_eq!("converted 6 CRLF line endings", result.changes[1].to_string());
+    assert_eq!("trimmed trailing whitespace from 1 line",
+               Change::TrimmedTrailingWhitespace(1).to_string());
+    assert!(!result.changes[2].is_notable());
+    assert!(result.changes[0].is_notable());
+  }
+}
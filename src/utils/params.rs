@@ -0,0 +1,65 @@
+//! Named parameters that change what a solver computes, such as how many
+//! elves day1 adds up.
+//!
+//! The runner installs the parameters from the command line and the config
+//! file, and the solvers look them up with a default, so they work the same
+//! when nobody set anything. Like progress reporting, the parameters belong
+//! to the thread that installed them.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+thread_local! {
+  static PARAMS: RefCell<BTreeMap<String, String>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Split a "name=value" argument into its name and value.
+pub fn parse_assignment(arg: &str) -> Result<(String, String), String> {
+  match arg.split_once('=') {
+    Some((name, value)) if !name.trim().is_empty() =>
+      Ok((name.trim().to_string(), value.trim().to_string())),
+    _ => Err(format!("expected name=value, but got '{arg}'")),
+  }
+}
+
+/// Use the given parameters for the following solvers.
+pub fn install(params: BTreeMap<String, String>) {
+  PARAMS.set(params);
+}
+
+/// Go back to the defaults.
+pub fn uninstall() {
+  PARAMS.take();
+}
+
+/// Get the parameter's value or the default if it isn't set. A value that
+/// can't be parsed is an error in the settings, so it panics like a bad
+/// input does.
+pub fn get<T: FromStr>(name: &str, default: T) -> T {
+  PARAMS.with_borrow(|params| match params.get(name) {
+    Some(value) => value.parse()
+      .unwrap_or_else(|_| panic!("Can't parse parameter {name}='{value}'")),
+    None => default,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+  use crate::utils::params;
+
+  #[test]
+  fn test_params() {
+    assert_eq!(Ok(("day1.top".to_string(), "5".to_string())),
+               params::parse_assignment("day1.top = 5"));
+    assert!(params::parse_assignment("day1.top").is_err());
+    assert!(params::parse_assignment("=5").is_err());
+    assert_eq!(3, params::get("day1.top", 3));
+    params::install(BTreeMap::from([("day1.top".to_string(), "5".to_string())]));
+    assert_eq!(5, params::get("day1.top", 3));
+    assert_eq!(7, params::get("day2.moves", 7));
+    params::uninstall();
+    assert_eq!(3, params::get("day1.top", 3));
+  }
+}
@@ -0,0 +1,234 @@
+//! A small set of parser combinators for the puzzle inputs.
+//!
+//! A `Cursor` walks through the input, and each combinator skips any
+//! whitespace before its token. That way, the parsers describe the text that
+//! they expect instead of counting words. Errors say where in the input
+//! parsing stopped and what was expected there.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Why and where the parsing failed.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct ParseError {
+  /// The line number, starting at 1.
+  pub line: usize,
+  /// The column in characters, starting at 1.
+  pub column: usize,
+  pub message: String,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// The position in the input that is being parsed.
+#[derive(Clone,Copy,Debug)]
+pub struct Cursor<'a> {
+  text: &'a str,
+  pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+  pub fn new(text: &'a str) -> Self {
+    Cursor{text, pos: 0}
+  }
+
+  /// The text that hasn't been parsed yet.
+  pub fn rest(&self) -> &'a str {
+    &self.text[self.pos..]
+  }
+
+  /// Build an error at the current position.
+  pub fn error<T>(&self, expected: &str) -> Result<T> {
+    let before = &self.text[..self.pos];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    let found: String = self.rest().split_whitespace().next()
+      .map(|w| format!("'{}'", w.chars().take(20).collect::<String>()))
+      .unwrap_or_else(|| "the end of the input".to_string());
+    Err(ParseError{line, column, message: format!("expected {expected}, found {found}")})
+  }
+
+  /// Skip over spaces, tabs, and newlines.
+  pub fn skip_whitespace(&mut self) {
+    let rest = self.rest();
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+
+  /// Is there anything left besides whitespace?
+  pub fn at_end(&mut self) -> bool {
+    self.skip_whitespace();
+    self.pos == self.text.len()
+  }
+
+  /// Require that there is nothing left besides whitespace.
+  pub fn end(&mut self) -> Result<()> {
+    if self.at_end() { Ok(()) } else { self.error("the end of the input") }
+  }
+
+  /// Try the parser and rewind if it fails.
+  pub fn attempt<T, F: FnOnce(&mut Self) -> Result<T>>(&mut self, parser: F) -> Result<T> {
+    let start = self.pos;
+    let result = parser(self);
+    if result.is_err() {
+      self.pos = start;
+    }
+    result
+  }
+
+  /// Match the given text. Each word of the literal may be preceded by
+  /// whitespace, and a word can't be followed directly by more letters, so
+  /// "valve" doesn't match the start of "valves".
+  pub fn literal(&mut self, literal: &str) -> Result<()> {
+    self.attempt(|cursor| {
+      for word in literal.split_whitespace() {
+        cursor.skip_whitespace();
+        let rest = cursor.rest();
+        if !rest.starts_with(word) ||
+            (word.ends_with(is_word_char) && rest[word.len()..].starts_with(is_word_char)) {
+          return cursor.error(&format!("'{literal}'"));
+        }
+        cursor.pos += word.len();
+      }
+      Ok(())
+    })
+  }
+
+  /// Match the literal if it is next and report whether it was.
+  pub fn accept(&mut self, literal: &str) -> bool {
+    self.literal(literal).is_ok()
+  }
+
+  /// Match the first of the literals that is next and return it.
+  pub fn one_of<'b>(&mut self, literals: &[&'b str]) -> Result<&'b str> {
+    match literals.iter().find(|&&lit| self.accept(lit)) {
+      Some(lit) => Ok(lit),
+      None => {
+        let names: Vec<String> = literals.iter().map(|l| format!("'{l}'")).collect();
+        self.error(&format!("one of {}", names.join(", ")))
+      }
+    }
+  }
+
+  /// Parse a run of letters, digits, and underscores.
+  pub fn word(&mut self) -> Result<&'a str> {
+    self.skip_whitespace();
+    let rest = self.rest();
+    let len = rest.find(|ch| !is_word_char(ch)).unwrap_or(rest.len());
+    if len == 0 {
+      return self.error("a word")
+    }
+    self.pos += len;
+    Ok(&rest[..len])
+  }
+
+  /// Parse an integer with an optional minus sign.
+  pub fn integer<T: FromStr>(&mut self) -> Result<T> {
+    self.skip_whitespace();
+    let rest = self.rest();
+    let sign = usize::from(rest.starts_with('-'));
+    let len = sign + rest[sign..].find(|ch: char| !ch.is_ascii_digit())
+      .unwrap_or(rest.len() - sign);
+    if len == sign {
+      return self.error("an integer")
+    }
+    match rest[..len].parse() {
+      Ok(value) => {
+        self.pos += len;
+        Ok(value)
+      },
+      Err(_) => self.error("an integer in range"),
+    }
+  }
+
+  /// Parse `key=value` using the given parser for the value.
+  pub fn field<T, F: FnOnce(&mut Self) -> Result<T>>(&mut self, key: &str,
+                                                      value: F) -> Result<T> {
+    self.literal(key)?;
+    self.literal("=")?;
+    value(self)
+  }
+
+  /// Parse one or more items with the separator between them.
+  pub fn separated<T, F: FnMut(&mut Self) -> Result<T>>(&mut self, separator: &str,
+                                                         mut item: F) -> Result<Vec<T>> {
+    let mut result = vec![item(self)?];
+    while self.accept(separator) {
+      result.push(item(self)?);
+    }
+    Ok(result)
+  }
+
+  /// Parse items until the end of the input.
+  pub fn repeat<T, F: FnMut(&mut Self) -> Result<T>>(&mut self,
+                                                      mut item: F) -> Result<Vec<T>> {
+    let mut result = Vec::new();
+    while !self.at_end() {
+      result.push(item(self)?);
+    }
+    Ok(result)
+  }
+}
+
+fn is_word_char(ch: char) -> bool {
+  ch.is_alphanumeric() || ch == '_'
+}
+
+/// Parse the whole input with the given parser.
+pub fn parse<'a, T, F: FnOnce(&mut Cursor<'a>) -> Result<T>>(input: &'a str,
+                                                             parser: F) -> Result<T> {
+  let mut cursor = Cursor::new(input);
+  let result = parser(&mut cursor)?;
+  cursor.end()?;
+  Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::parse::{parse, Cursor, ParseError, Result};
+
+  fn point(cursor: &mut Cursor) -> Result<(i64, i64)> {
+    let x = cursor.field("x", Cursor::integer)?;
+    cursor.literal(",")?;
+    Ok((x, cursor.field("y", Cursor::integer)?))
+  }
+
+  #[test]
+  fn test_combinators() {
+    let result = parse("at x=2, y=-18\nat x=0,y=1", |c| c.repeat(|c| {
+      c.literal("at")?;
+      point(c)
+    }));
+    assert_eq!(Ok(vec![(2, -18), (0, 1)]), result);
+    let result = parse("valves DD, II, BB", |c| {
+      c.one_of(&["valve", "valves"])?;
+      c.separated(",", Cursor::word)
+    });
+    assert_eq!(Ok(vec!["DD", "II", "BB"]), result);
+  }
+
+  #[test]
+  fn test_errors() {
+    let result = parse("at x=2, y=18\nat x=0, z=1", |c| c.repeat(|c| {
+      c.literal("at")?;
+      point(c)
+    }));
+    assert_eq!(Err(ParseError{line: 2, column: 9,
+                              message: "expected 'y', found 'z=1'".to_string()}), result);
+    assert_eq!("line 1, column 1: expected an integer, found 'abc'",
+               parse("abc", Cursor::integer::<u8>).unwrap_err().to_string());
+    assert_eq!("line 1, column 1: expected an integer in range, found '300'",
+               parse("300", Cursor::integer::<u8>).unwrap_err().to_string());
+    assert_eq!("line 1, column 7: expected the end of the input, found 'b'",
+               parse("valve b", |c| c.literal("valve")).unwrap_err().to_string());
+    assert!(parse("valves", |c| c.literal("valve")).is_err());
+    assert!(parse("tunnel leads", |c| c.literal("tunnel   leads")).is_ok());
+  }
+}
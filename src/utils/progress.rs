@@ -0,0 +1,183 @@
+//! Progress reporting for long-running solvers.
+//!
+//! Solvers call `report` with a closure that describes how far they have
+//! gotten. Unless the runner has installed a sink, the call is just a check of
+//! a flag, so it is cheap enough to leave in the solvers. The sink belongs to
+//! the thread that installed it.
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// The minimum time between updates sent to the sink.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the progress messages are displayed.
+pub trait ProgressSink {
+  /// Show the latest message for the given phase.
+  fn update(&mut self, label: &str, message: &str);
+  /// Remove the last message, because the phase finished.
+  fn clear(&mut self);
+}
+
+/// Show the progress as a single status line on stderr that is overwritten
+/// by each update.
+pub struct TerminalSink;
+
+impl ProgressSink for TerminalSink {
+  fn update(&mut self, label: &str, message: &str) {
+    let mut err = io::stderr().lock();
+    let _ = write!(err, "\r\x1b[2K{label}: {message}");
+    let _ = err.flush();
+  }
+
+  fn clear(&mut self) {
+    let mut err = io::stderr().lock();
+    let _ = write!(err, "\r\x1b[2K");
+    let _ = err.flush();
+  }
+}
+
+struct Reporter {
+  sink: Box<dyn ProgressSink>,
+  label: String,
+  last_update: Option<Instant>,
+  shown: bool,
+}
+
+thread_local! {
+  static ENABLED: Cell<bool> = const { Cell::new(false) };
+  static REPORTER: RefCell<Option<Reporter>> = const { RefCell::new(None) };
+}
+
+/// Send progress messages to the given sink.
+pub fn install(sink: Box<dyn ProgressSink>) {
+  REPORTER.with_borrow_mut(|r| *r =
+    Some(Reporter{sink, label: String::new(), last_update: None, shown: false}));
+  ENABLED.set(true);
+}
+
+/// Stop reporting progress.
+pub fn uninstall() {
+  ENABLED.set(false);
+  if let Some(mut reporter) = REPORTER.take() {
+    if reporter.shown {
+      reporter.sink.clear();
+    }
+  }
+}
+
+/// Is anyone listening to the progress reports?
+pub fn is_enabled() -> bool {
+  ENABLED.get()
+}
+
+/// Start a new phase, such as "day16 part 2", that the following
+/// messages belong to.
+pub fn start(label: &str) {
+  if is_enabled() {
+    REPORTER.with_borrow_mut(|r| if let Some(reporter) = r {
+      reporter.label = label.to_string();
+      reporter.last_update = None;
+    });
+  }
+}
+
+/// Finish the current phase and clear its last message.
+pub fn finish() {
+  if is_enabled() {
+    REPORTER.with_borrow_mut(|r| if let Some(reporter) = r {
+      if reporter.shown {
+        reporter.sink.clear();
+        reporter.shown = false;
+      }
+    });
+  }
+}
+
+/// Report the solver's progress. The message is only built if there is a
+/// sink and the previous update was long enough ago.
+pub fn report<F: FnOnce() -> String>(message: F) {
+  if !is_enabled() {
+    return
+  }
+  REPORTER.with_borrow_mut(|r| if let Some(reporter) = r {
+    let now = Instant::now();
+    if reporter.last_update.is_some_and(|last| now - last < UPDATE_INTERVAL) {
+      return
+    }
+    reporter.last_update = Some(now);
+    reporter.shown = true;
+    reporter.sink.update(&reporter.label, &message());
+  });
+}
+
+/// Count events, such as states explored, and report progress every
+/// `interval` events.
+pub struct Counter {
+  count: u64,
+  interval: u64,
+}
+
+impl Counter {
+  /// Create a counter that reports every `interval` events, which
+  /// should be a power of two.
+  pub fn new(interval: u64) -> Self {
+    Counter{count: 0, interval: interval.next_power_of_two()}
+  }
+
+  /// Count an event and, if it is time, report the message built from
+  /// the total.
+  pub fn tick<F: FnOnce(u64) -> String>(&mut self, message: F) {
+    self.count += 1;
+    if self.count & (self.interval - 1) == 0 {
+      report(|| message(self.count));
+    }
+  }
+
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  use crate::utils::progress::{self, Counter, ProgressSink};
+
+  struct Recorder {
+    messages: Rc<RefCell<Vec<String>>>,
+  }
+
+  impl ProgressSink for Recorder {
+    fn update(&mut self, label: &str, message: &str) {
+      self.messages.borrow_mut().push(format!("{label}: {message}"));
+    }
+
+    fn clear(&mut self) {
+      self.messages.borrow_mut().push("clear".to_string());
+    }
+  }
+
+  #[test]
+  fn test_report() {
+    let messages = Rc::new(RefCell::new(Vec::new()));
+    progress::install(Box::new(Recorder{messages: messages.clone()}));
+    progress::start("day19 part 2");
+    progress::report(|| "blueprint 1/3".to_string());
+    // too soon after the previous update
+    progress::report(|| "blueprint 2/3".to_string());
+    progress::finish();
+    progress::start("day16 part 1");
+    let mut counter = Counter::new(4);
+    for _ in 0..10 {
+      counter.tick(|n| format!("{n} states"));
+    }
+    assert_eq!(10, counter.count());
+    progress::uninstall();
+    progress::report(|| "ignored".to_string());
+    assert_eq!(vec!["day19 part 2: blueprint 1/3", "clear", "day16 part 1: 4 states", "clear"],
+               *messages.borrow());
+  }
+}
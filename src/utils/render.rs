@@ -0,0 +1,217 @@
+//! Rendering frames of the simulations, so that they can be watched while
+//! debugging.
+//!
+//! Solvers call `frame` with a closure that draws the current state as a grid
+//! of characters. Like progress reporting, the call is just a check of a flag
+//! unless the runner has installed a sink, and the sink belongs to the thread
+//! that installed it. Only every Nth frame is drawn and sent to the sink.
+
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use crate::utils::grid::Grid;
+
+/// A picture of a simulation's state.
+pub type Frame = Grid<char>;
+
+/// Where the frames are displayed.
+pub trait FrameSink {
+  /// Show the given frame, which is the `number`th frame of the phase.
+  fn frame(&mut self, label: &str, number: usize, frame: &Frame) -> io::Result<()>;
+}
+
+/// Draw the frames as text on stderr, redrawing the screen for each one.
+pub struct TerminalSink;
+
+impl TerminalSink {
+  /// How long each frame stays on the screen.
+  const FRAME_DELAY: Duration = Duration::from_millis(50);
+}
+
+impl FrameSink for TerminalSink {
+  fn frame(&mut self, label: &str, number: usize, frame: &Frame) -> io::Result<()> {
+    let mut err = io::stderr().lock();
+    write!(err, "\x1b[H\x1b[2J{label} frame {number}\n{frame}")?;
+    err.flush()?;
+    thread::sleep(Self::FRAME_DELAY);
+    Ok(())
+  }
+}
+
+/// Write each frame as a PPM image in a directory. The files are named
+/// after the phase and numbered, such as `day14-part-1-00012.ppm`, so that
+/// tools like ffmpeg can turn them into a video.
+pub struct PpmSink {
+  dir: PathBuf,
+  /// The width and height in pixels of each cell.
+  scale: usize,
+}
+
+impl PpmSink {
+  pub fn new(dir: &str, scale: usize) -> io::Result<Self> {
+    fs::create_dir_all(dir)?;
+    Ok(PpmSink{dir: PathBuf::from(dir), scale})
+  }
+}
+
+impl FrameSink for PpmSink {
+  fn frame(&mut self, label: &str, number: usize, frame: &Frame) -> io::Result<()> {
+    let name = format!("{}-{number:05}.ppm", label.replace(' ', "-"));
+    fs::write(self.dir.join(name), encode_ppm(frame, self.scale))
+  }
+}
+
+/// The color of each character in the images.
+fn color(ch: char) -> [u8; 3] {
+  match ch {
+    '.' | ' ' => [0, 0, 0],
+    '#' => [128, 128, 128],
+    'o' => [230, 200, 80],
+    '<' | '>' | '^' | 'v' => [120, 170, 255],
+    '2'..='9' => [60, 90, 200],
+    'E' => [80, 220, 80],
+    'H' => [230, 60, 60],
+    's' => [255, 255, 255],
+    // give every other character its own color
+    _ => {
+      let hash = (ch as u32).wrapping_mul(0x9e37_79b9);
+      [(hash >> 24) as u8 | 0x40, (hash >> 16) as u8 | 0x40, (hash >> 8) as u8 | 0x40]
+    }
+  }
+}
+
+/// Convert the frame into a binary PPM image with each cell drawn as a
+/// `scale` by `scale` square.
+pub fn encode_ppm(frame: &Frame, scale: usize) -> Vec<u8> {
+  let (width, height) = (frame.width() * scale, frame.height() * scale);
+  let mut result = format!("P6\n{width} {height}\n255\n").into_bytes();
+  for row in frame.rows() {
+    let line: Vec<u8> = row.iter()
+      .flat_map(|&ch| std::iter::repeat_n(color(ch), scale).flatten())
+      .collect();
+    for _ in 0..scale {
+      result.extend(&line);
+    }
+  }
+  result
+}
+
+struct Renderer {
+  sink: Box<dyn FrameSink>,
+  /// Draw one of every `every` frames.
+  every: usize,
+  label: String,
+  /// The number of calls to `frame` in this phase.
+  steps: usize,
+  /// The number of frames sent to the sink in this phase.
+  frames: usize,
+}
+
+thread_local! {
+  static ENABLED: Cell<bool> = const { Cell::new(false) };
+  static RENDERER: RefCell<Option<Renderer>> = const { RefCell::new(None) };
+}
+
+/// Send every `every`th frame to the given sink.
+pub fn install(sink: Box<dyn FrameSink>, every: usize) {
+  RENDERER.with_borrow_mut(|r| *r = Some(Renderer{sink, every: every.max(1),
+    label: String::new(), steps: 0, frames: 0}));
+  ENABLED.set(true);
+}
+
+/// Stop rendering frames.
+pub fn uninstall() {
+  ENABLED.set(false);
+  RENDERER.take();
+}
+
+/// Is anyone watching the frames?
+pub fn is_enabled() -> bool {
+  ENABLED.get()
+}
+
+/// Start a new phase, such as "day14 part 1", that the following frames
+/// belong to.
+pub fn start(label: &str) {
+  if is_enabled() {
+    RENDERER.with_borrow_mut(|r| if let Some(renderer) = r {
+      renderer.label = label.to_string();
+      renderer.steps = 0;
+      renderer.frames = 0;
+    });
+  }
+}
+
+/// Record a step of the simulation. The frame is only drawn if there is a
+/// sink and this is one of the steps that it wants. If the sink fails, the
+/// error is reported and rendering stops.
+pub fn frame<F: FnOnce() -> Frame>(draw: F) {
+  if !is_enabled() {
+    return
+  }
+  let failed = RENDERER.with_borrow_mut(|r| {
+    let renderer = r.as_mut()?;
+    renderer.steps += 1;
+    if (renderer.steps - 1) % renderer.every != 0 {
+      return None
+    }
+    let number = renderer.frames;
+    renderer.frames += 1;
+    renderer.sink.frame(&renderer.label, number, &draw()).err()
+  });
+  if let Some(err) = failed {
+    eprintln!("Can't render frame, so rendering is off: {err}");
+    uninstall();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+  use std::io;
+  use std::rc::Rc;
+  use crate::utils::grid::Grid;
+  use crate::utils::render::{self, encode_ppm, Frame, FrameSink};
+
+  struct Recorder {
+    frames: Rc<RefCell<Vec<String>>>,
+  }
+
+  impl FrameSink for Recorder {
+    fn frame(&mut self, label: &str, number: usize, frame: &Frame) -> io::Result<()> {
+      self.frames.borrow_mut().push(format!("{label} {number}: {}", frame.row(0)[0]));
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_frames() {
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    render::install(Box::new(Recorder{frames: frames.clone()}), 3);
+    render::start("day9 part 1");
+    for ch in "abcdefg".chars() {
+      render::frame(|| Grid::new(1, 1, ch));
+    }
+    render::start("day9 part 2");
+    render::frame(|| Grid::new(1, 1, 'z'));
+    render::uninstall();
+    render::frame(|| panic!("not rendering"));
+    assert_eq!(vec!["day9 part 1 0: a", "day9 part 1 1: d", "day9 part 1 2: g",
+                    "day9 part 2 0: z"], *frames.borrow());
+  }
+
+  #[test]
+  fn test_ppm() {
+    let image = encode_ppm(&Grid::parse("#.", |ch| ch), 2);
+    let header = b"P6\n4 2\n255\n";
+    assert_eq!(header.len() + 4 * 2 * 3, image.len());
+    assert_eq!(header, &image[..header.len()]);
+    let row = [128, 128, 128, 128, 128, 128, 0, 0, 0, 0, 0, 0];
+    assert_eq!(&row, &image[header.len()..header.len() + 12]);
+    assert_eq!(&row, &image[header.len() + 12..]);
+  }
+}
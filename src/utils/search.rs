@@ -0,0 +1,305 @@
+//! Generic searches over the puzzles' state spaces.
+//!
+//! The shortest path searches work on a graph that generates the successors
+//! of each node, while branch and bound works on states that know how to
+//! branch and bound themselves.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::utils::progress;
+
+/// A graph where every edge has the same cost.
+pub trait Graph {
+  type Node: Clone + Eq + Hash;
+
+  /// The nodes that are one step away from the given one.
+  fn neighbors(&self, node: &Self::Node) -> impl Iterator<Item=Self::Node>;
+}
+
+/// A graph with a cost on each edge.
+pub trait WeightedGraph {
+  type Node: Clone + Eq + Hash;
+  type Cost: Copy + Default + Ord + Add<Output=Self::Cost>;
+
+  /// The nodes that are one step away from the given one and the cost of getting there.
+  fn edges(&self, node: &Self::Node) -> impl Iterator<Item=(Self::Node, Self::Cost)>;
+
+  /// A lower bound on the cost to reach a goal from the given node, which
+  /// A* uses to search toward the goal. It must never overestimate.
+  fn estimate(&self, _node: &Self::Node) -> Self::Cost {
+    Self::Cost::default()
+  }
+}
+
+/// Should the search remember how it reached each node so that it can return
+/// the path? That takes more memory, so it is only done when asked.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Path {
+  Keep,
+  Skip,
+}
+
+/// The result of a successful search.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Found<N, C> {
+  pub goal: N,
+  pub cost: C,
+  /// The nodes from the start to the goal, inclusive, if it was kept.
+  pub path: Option<Vec<N>>,
+}
+
+/// Follow the parent links back from the goal to build the path.
+fn build_path<N: Clone>(nodes: &[N], parents: &[usize], goal: usize) -> Vec<N> {
+  let mut result = vec![nodes[goal].clone()];
+  let mut current = goal;
+  while parents[current] != current {
+    current = parents[current];
+    result.push(nodes[current].clone());
+  }
+  result.reverse();
+  result
+}
+
+/// Find the fewest steps from any of the starting nodes to a goal with a
+/// breadth first search.
+pub fn bfs<G: Graph, F: FnMut(&G::Node) -> bool>(
+    graph: &G, starts: impl IntoIterator<Item=G::Node>, mut is_goal: F,
+    path: Path) -> Option<Found<G::Node, usize>> {
+  let mut nodes: Vec<G::Node> = Vec::new();
+  let mut parents: Vec<usize> = Vec::new();
+  let mut seen: HashMap<G::Node, usize> = HashMap::new();
+  let mut queue = VecDeque::new();
+  for start in starts {
+    if !seen.contains_key(&start) {
+      let idx = seen.len();
+      seen.insert(start.clone(), idx);
+      if path == Path::Keep {
+        nodes.push(start.clone());
+        parents.push(idx);
+      }
+      queue.push_back((start, idx, 0));
+    }
+  }
+  while let Some((node, idx, steps)) = queue.pop_front() {
+    if is_goal(&node) {
+      let path = (path == Path::Keep).then(|| build_path(&nodes, &parents, idx));
+      return Some(Found{goal: node, cost: steps, path})
+    }
+    for next in graph.neighbors(&node) {
+      if !seen.contains_key(&next) {
+        let next_idx = seen.len();
+        seen.insert(next.clone(), next_idx);
+        if path == Path::Keep {
+          nodes.push(next.clone());
+          parents.push(idx);
+        }
+        queue.push_back((next, next_idx, steps + 1));
+      }
+    }
+  }
+  None
+}
+
+/// The shared implementation of Dijkstra's algorithm and A*. The nodes are
+/// numbered as they are found so that the heap doesn't need to order them.
+fn best_first<G: WeightedGraph, F: FnMut(&G::Node) -> bool>(
+    graph: &G, starts: impl IntoIterator<Item=G::Node>, mut is_goal: F,
+    path: Path, use_estimate: bool) -> Option<Found<G::Node, G::Cost>> {
+  let estimate = |node: &G::Node| {
+    if use_estimate { graph.estimate(node) } else { G::Cost::default() }
+  };
+  let mut nodes: Vec<G::Node> = Vec::new();
+  let mut costs: Vec<G::Cost> = Vec::new();
+  let mut parents: Vec<usize> = Vec::new();
+  let mut index: HashMap<G::Node, usize> = HashMap::new();
+  let mut heap = BinaryHeap::new();
+  for start in starts {
+    if !index.contains_key(&start) {
+      let idx = nodes.len();
+      index.insert(start.clone(), idx);
+      heap.push(Reverse((estimate(&start), idx)));
+      nodes.push(start);
+      costs.push(G::Cost::default());
+      parents.push(idx);
+    }
+  }
+  let mut done = vec![false; nodes.len()];
+  while let Some(Reverse((_, idx))) = heap.pop() {
+    if done[idx] {
+      continue
+    }
+    done[idx] = true;
+    let cost = costs[idx];
+    if is_goal(&nodes[idx]) {
+      let path = (path == Path::Keep).then(|| build_path(&nodes, &parents, idx));
+      return Some(Found{goal: nodes[idx].clone(), cost, path})
+    }
+    let current = nodes[idx].clone();
+    for (next, edge) in graph.edges(&current) {
+      let next_cost = cost + edge;
+      let next_idx = match index.get(&next) {
+        Some(&i) if done[i] || costs[i] <= next_cost => continue,
+        Some(&i) => i,
+        None => {
+          let i = nodes.len();
+          index.insert(next.clone(), i);
+          nodes.push(next);
+          costs.push(next_cost);
+          parents.push(idx);
+          done.push(false);
+          i
+        }
+      };
+      costs[next_idx] = next_cost;
+      parents[next_idx] = idx;
+      heap.push(Reverse((next_cost + estimate(&nodes[next_idx]), next_idx)));
+    }
+  }
+  None
+}
+
+/// Find the cheapest path from any of the starting nodes to a goal with
+/// Dijkstra's algorithm.
+pub fn dijkstra<G: WeightedGraph, F: FnMut(&G::Node) -> bool>(
+    graph: &G, starts: impl IntoIterator<Item=G::Node>, is_goal: F,
+    path: Path) -> Option<Found<G::Node, G::Cost>> {
+  best_first(graph, starts, is_goal, path, false)
+}
+
+/// Find the cheapest path from any of the starting nodes to a goal with A*,
+/// using the graph's estimate to search toward the goal first.
+pub fn astar<G: WeightedGraph, F: FnMut(&G::Node) -> bool>(
+    graph: &G, starts: impl IntoIterator<Item=G::Node>, is_goal: F,
+    path: Path) -> Option<Found<G::Node, G::Cost>> {
+  best_first(graph, starts, is_goal, path, true)
+}
+
+/// The states for a branch and bound search for the highest score.
+pub trait Bounded: Clone {
+  /// The problem description that the states need to branch.
+  type Context;
+  type Score: Copy + Default + Display + Ord;
+
+  /// Which states are reachable from this one?
+  fn branches(&self, context: &Self::Context) -> Vec<Self>;
+  /// What is the score for this state?
+  fn score(&self) -> Self::Score;
+  /// What is the highest score that any state reachable from this one could
+  /// have? The closer this is, the more of the search is pruned.
+  fn upper_bound(&self, context: &Self::Context) -> Self::Score;
+}
+
+/// Find the state with the highest score that is reachable from the initial
+/// one. The branches are searched depth first, so that a good score is found
+/// early, and branches that can't beat the best so far are dropped.
+pub fn maximize<T: Bounded>(context: &T::Context, initial: T) -> T {
+  let mut best = initial.clone();
+  let mut max = initial.score();
+  let mut pending = vec![initial];
+  let mut explored = progress::Counter::new(1 << 16);
+  while let Some(state) = pending.pop() {
+    explored.tick(|n| format!("{n} states explored, best {max}"));
+    if state.score() > max {
+      max = state.score();
+      best = state.clone();
+    }
+    pending.extend(state.branches(context).into_iter()
+      .filter(|s| s.upper_bound(context) > max));
+  }
+  best
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::search::{astar, bfs, dijkstra, maximize, Bounded, Graph, Path,
+                             WeightedGraph};
+
+  /// A line of nodes 0..10 where you can step by 1 for 1 or jump by 3 for 2.
+  struct Line;
+
+  impl Graph for Line {
+    type Node = i32;
+
+    fn neighbors(&self, node: &i32) -> impl Iterator<Item=i32> {
+      [node - 1, node + 1, node + 3].into_iter().filter(|n| (0..10).contains(n))
+    }
+  }
+
+  impl WeightedGraph for Line {
+    type Node = i32;
+    type Cost = u32;
+
+    fn edges(&self, node: &i32) -> impl Iterator<Item=(i32, u32)> {
+      [(node - 1, 1), (node + 1, 1), (node + 3, 2)].into_iter()
+        .filter(|(n, _)| (0..10).contains(n))
+    }
+
+    fn estimate(&self, node: &i32) -> u32 {
+      // the jumps cost 2/3 per node
+      ((9 - node).unsigned_abs() * 2).div_ceil(3)
+    }
+  }
+
+  #[test]
+  fn test_bfs() {
+    let found = bfs(&Line, [0], |&n| n == 8, Path::Keep).unwrap();
+    assert_eq!(4, found.cost);
+    assert_eq!(5, found.path.unwrap().len());
+    assert_eq!(None, bfs(&Line, [0], |&n| n == 10, Path::Skip));
+    assert_eq!(0, bfs(&Line, [5, 3], |&n| n == 3, Path::Skip).unwrap().cost);
+  }
+
+  #[test]
+  fn test_dijkstra() {
+    let found = dijkstra(&Line, [0], |&n| n == 8, Path::Keep).unwrap();
+    assert_eq!(6, found.cost);
+    let path = found.path.unwrap();
+    assert_eq!((Some(&0), Some(&8)), (path.first(), path.last()));
+    let found = astar(&Line, [0], |&n| n == 9, Path::Skip).unwrap();
+    assert_eq!((9, 6, None), (found.goal, found.cost, found.path));
+  }
+
+  /// Pick items with (weight, value) to fill a knapsack.
+  #[derive(Clone,Debug)]
+  struct Knapsack {
+    next: usize,
+    room: u32,
+    value: u32,
+  }
+
+  impl Bounded for Knapsack {
+    type Context = Vec<(u32, u32)>;
+    type Score = u32;
+
+    fn branches(&self, items: &Self::Context) -> Vec<Self> {
+      let mut result = Vec::new();
+      if let Some(&(weight, value)) = items.get(self.next) {
+        result.push(Knapsack{next: self.next + 1, ..self.clone()});
+        if weight <= self.room {
+          result.push(Knapsack{next: self.next + 1, room: self.room - weight,
+            value: self.value + value});
+        }
+      }
+      result
+    }
+
+    fn score(&self) -> u32 {
+      self.value
+    }
+
+    fn upper_bound(&self, items: &Self::Context) -> u32 {
+      self.value + items[self.next..].iter().map(|i| i.1).sum::<u32>()
+    }
+  }
+
+  #[test]
+  fn test_maximize() {
+    let items = vec![(5, 10), (4, 40), (6, 30), (3, 50)];
+    let best = maximize(&items, Knapsack{next: 0, room: 10, value: 0});
+    assert_eq!(90, best.value);
+  }
+}
@@ -0,0 +1,163 @@
+//! Stepping through the simulations by hand.
+//!
+//! Days whose solutions run a simulation one step at a time implement
+//! `Simulation`, and `debug` reads commands that step the simulation and
+//! print its state, so that a failing input can be followed step by step.
+
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// A puzzle that is solved by repeating a step until it is done.
+pub trait Simulation {
+  /// Do the next step.
+  fn step(&mut self);
+  /// Is the simulation finished?
+  fn is_done(&self) -> bool;
+  /// Show the current state to a person.
+  fn describe(&self) -> String;
+}
+
+/// Build a day's simulation for the given part from its input and pass it
+/// to the callback.
+pub type Builder = &'static dyn Fn(&str, usize, &mut dyn FnMut(&mut dyn Simulation));
+
+/// The commands that the debugger understands.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub enum Command {
+  /// Do the given number of steps.
+  Step(usize),
+  /// Step until the description contains the text.
+  RunUntil(String),
+  /// Step until the simulation is done.
+  Run,
+  Print,
+  Help,
+  Quit,
+}
+
+impl FromStr for Command {
+  type Err = String;
+
+  fn from_str(line: &str) -> Result<Self, Self::Err> {
+    let line = line.trim();
+    let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    match word {
+      "step" | "s" if rest.is_empty() => Ok(Command::Step(1)),
+      "step" | "s" => rest.parse().map(Command::Step)
+        .map_err(|_| format!("can't parse step count '{rest}'")),
+      "run-until" | "u" if rest.is_empty() => Err("run-until needs some text".to_string()),
+      "run-until" | "u" => Ok(Command::RunUntil(rest.to_string())),
+      "run" | "r" => Ok(Command::Run),
+      "print" | "p" => Ok(Command::Print),
+      "help" | "h" | "?" => Ok(Command::Help),
+      "quit" | "q" => Ok(Command::Quit),
+      _ => Err(format!("unknown command '{word}', try help")),
+    }
+  }
+}
+
+const HELP: &str = "\
+step [N]          do N steps (default 1)
+run-until TEXT    step until the state's description contains TEXT
+run               step until the simulation is done
+print             show the current state
+quit              stop debugging
+";
+
+/// Step the simulation until it is done or the condition is true, returning
+/// the number of steps done.
+fn run<F: FnMut(&dyn Simulation) -> bool>(sim: &mut dyn Simulation, mut stop: F) -> usize {
+  let mut steps = 0;
+  while !sim.is_done() && !stop(sim) {
+    sim.step();
+    steps += 1;
+  }
+  steps
+}
+
+/// Read commands, one per line, and apply them to the simulation, writing
+/// the results to `out`. Stops at the end of the input or a quit command.
+pub fn debug(sim: &mut dyn Simulation, input: impl BufRead,
+             mut out: impl Write) -> io::Result<()> {
+  let mut steps = 0;
+  writeln!(out, "{}", sim.describe())?;
+  for line in input.lines() {
+    let line = line?;
+    if line.trim().is_empty() {
+      continue
+    }
+    match line.parse() {
+      Ok(Command::Step(count)) => {
+        let mut remaining = count;
+        steps += run(sim, |_| if remaining == 0 { true } else { remaining -= 1; false });
+        writeln!(out, "step {steps}\n{}", sim.describe())?;
+      }
+      Ok(Command::RunUntil(text)) => {
+        steps += run(sim, |s| s.describe().contains(&text));
+        writeln!(out, "step {steps}\n{}", sim.describe())?;
+      }
+      Ok(Command::Run) => {
+        steps += run(sim, |_| false);
+        writeln!(out, "step {steps}\n{}", sim.describe())?;
+      }
+      Ok(Command::Print) => writeln!(out, "step {steps}\n{}", sim.describe())?,
+      Ok(Command::Help) => write!(out, "{HELP}")?,
+      Ok(Command::Quit) => break,
+      Err(message) => writeln!(out, "{message}")?,
+    }
+    if sim.is_done() {
+      writeln!(out, "(done)")?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::simulation::{debug, Command, Simulation};
+
+  /// Count up to a limit.
+  struct Counter {
+    value: usize,
+    limit: usize,
+  }
+
+  impl Simulation for Counter {
+    fn step(&mut self) {
+      self.value += 1;
+    }
+
+    fn is_done(&self) -> bool {
+      self.value == self.limit
+    }
+
+    fn describe(&self) -> String {
+      format!("value = {}", self.value)
+    }
+  }
+
+  #[test]
+  fn test_commands() {
+    assert_eq!(Ok(Command::Step(1)), "step".parse());
+    assert_eq!(Ok(Command::Step(12)), " s 12 ".parse());
+    assert_eq!(Ok(Command::RunUntil("x = 3".to_string())), "run-until x = 3".parse());
+    assert!("step many".parse::<Command>().is_err());
+    assert!("jump".parse::<Command>().is_err());
+  }
+
+  #[test]
+  fn test_debug() {
+    let mut counter = Counter{value: 0, limit: 30};
+    let mut out = Vec::new();
+    debug(&mut counter, "step 3\nrun-until = 12\n\nprint\nfly\nrun\nstep\nquit\nstep"
+      .as_bytes(), &mut out).unwrap();
+    assert_eq!("value = 0\n\
+                step 3\nvalue = 3\n\
+                step 12\nvalue = 12\n\
+                step 12\nvalue = 12\n\
+                unknown command 'fly', try help\n\
+                step 30\nvalue = 30\n(done)\n\
+                step 30\nvalue = 30\n(done)\n", String::from_utf8(out).unwrap());
+  }
+}
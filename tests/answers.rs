@@ -0,0 +1,49 @@
+//! Run every day on each input directory that has an answers.yml and check
+//! that the answers still match the stored ones.
+
+use omalley_aoc2022::{FUNCS, NAMES, utils};
+use omalley_aoc2022::utils::answers::{Answers, Check};
+use std::fs;
+use std::path::Path;
+
+/// Find the directories in the crate that have stored answers.
+fn answer_dirs() -> Vec<String> {
+  let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+  let mut result: Vec<String> = fs::read_dir(root).expect("Can't list the crate")
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| path.join("answers.yml").is_file())
+    .map(|path| path.to_string_lossy().to_string())
+    .collect();
+  result.sort();
+  result
+}
+
+/// Run the days on the inputs in the directory and describe any answers
+/// that don't match.
+fn check_dir(dir: &str) -> Vec<String> {
+  let answers = Answers::read(dir);
+  let inputs = utils::read_inputs(dir, NAMES).expect("Can't read input dir");
+  let mut failures = Vec::new();
+  for (func, raw) in FUNCS.iter().zip(inputs) {
+    let result = func(&utils::normalize::normalize(&raw).text);
+    for (part, answer) in result.get_answers().iter().enumerate() {
+      match answers.check(&result.day, part, answer) {
+        Check::Mismatch(expected) =>
+          failures.push(format!("{dir} {} part {}: expected {expected}, got {answer}",
+                                result.day, part + 1)),
+        Check::Error => failures.push(format!("{dir} {answer}")),
+        _ => {},
+      }
+    }
+  }
+  failures
+}
+
+#[test]
+fn test_known_answers() {
+  let dirs = answer_dirs();
+  assert!(!dirs.is_empty(), "No directories with answers.yml");
+  let failures: Vec<String> = dirs.iter().flat_map(|dir| check_dir(dir)).collect();
+  assert!(failures.is_empty(), "Answers changed:\n{}", failures.join("\n"));
+}